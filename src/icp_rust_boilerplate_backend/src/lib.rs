@@ -2,13 +2,43 @@
 extern crate serde;
 use candid::{CandidType, Decode, Encode, Principal};
 use ic_cdk::api::caller;
+use ic_cdk::api::management_canister::main::raw_rand;
+use ic_cdk_timers::TimerId;
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
 use ic_stable_structures::{BoundedStorable, Cell, DefaultMemoryImpl, StableBTreeMap, Storable};
-use std::{borrow::Cow, cell::RefCell};
+use sha2::{Digest, Sha256};
+use std::{borrow::Cow, cell::RefCell, collections::HashMap, time::Duration};
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 type IdCell = Cell<u64, Memory>;
 
+// How long a guest can hold a registry item before the reservation lapses
+const REGISTRY_RESERVATION_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+const NANOS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// How long after the wedding the couple keeps guests' personal data
+const GUEST_DATA_RETENTION_DAYS: u64 = 90;
+
+const MAX_GUEST_PAGE_SIZE: u64 = 100;
+
+// Longest a single timeline item can run, and the most one delay can push the day back
+const MAX_TIMELINE_ITEM_MINUTES: u64 = 24 * 60;
+const MAX_TIMELINE_DELAY_MINUTES: u64 = 24 * 60;
+
+// How often overdue tasks and timeline items are swept, and how many weddings per sweep
+const OVERDUE_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const OVERDUE_SWEEP_BATCH_SIZE: usize = 50;
+
+// Tasks due within this many days need attention
+const TASK_DUE_SOON_DAYS: i64 = 7;
+
+// Longest a single task can take
+const MAX_TASK_DURATION_DAYS: u64 = 3 * 365;
+
+// Furthest ahead of the wedding a checklist item can be scheduled
+const MAX_CHECKLIST_DAYS_BEFORE: u64 = 5 * 365;
+
 /**
  * Enumerations
  */
@@ -33,7 +63,7 @@ enum Category {
 }
 
 // Table Assignment Enum
-#[derive(CandidType, Clone, Serialize, Deserialize, Default, Debug)]
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
 enum TableAssignment {
     #[default]
     VIPTable,
@@ -42,6 +72,129 @@ enum TableAssignment {
     Unassigned,
 }
 
+// Guest Age Group Enum
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum AgeGroup {
+    #[default]
+    Adult,
+    Teen,
+    Child,
+    Infant,
+}
+
+// Which side of the couple a guest is from
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum GuestSide {
+    PartnerA,
+    PartnerB,
+    #[default]
+    Mutual,
+}
+
+// Guest Relationship to the couple
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum Relationship {
+    Family,
+    Friend,
+    Colleague,
+    #[default]
+    Other,
+}
+
+// Registry Item Priority Enum
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum RegistryPriority {
+    High,
+    #[default]
+    Medium,
+    Low,
+}
+
+// Registry Item Kind Enum
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum RegistryItemKind {
+    #[default]
+    Item,
+    Fund, // Cash fund, e.g. honeymoon or house deposit
+}
+
+// Registry Item Category Enum
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum RegistryCategory {
+    Kitchen,
+    Dining,
+    Home,
+    Bedding,
+    Bath,
+    Electronics,
+    Outdoor,
+    Travel,
+    Experience,
+    #[default]
+    Other,
+}
+
+// Gift Source Enum
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum GiftSource {
+    RegistryPurchase,
+    RegistryContribution,
+    #[default]
+    OffRegistry,
+}
+
+// Thank-you Note Status Enum
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum ThankYouStatus {
+    #[default]
+    Pending,
+    Written,
+    Sent,
+}
+
+// Major food allergens caterers need to plan for
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Debug)]
+enum Allergen {
+    Gluten,
+    Dairy,
+    Egg,
+    Peanut,
+    TreeNut,
+    Soy,
+    Fish,
+    Shellfish,
+    Molluscs,
+    Sesame,
+    Mustard,
+    Celery,
+    Lupin,
+    Sulphites,
+}
+
+// File format for guest list import and export
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum GuestListFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+// Printable format for run sheets
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum RunSheetFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+// What happens to guests who haven't responded by the RSVP deadline
+#[derive(CandidType, Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
+enum RsvpDeadlineAction {
+    #[default]
+    Flag, // Marked "no-response" for the couple to follow up
+    Decline,
+}
+
 /**
  * Core Types
  */
@@ -86,11 +239,90 @@ struct VendorBooking {
 // Timeline Item Record
 #[derive(CandidType, Clone, Serialize, Deserialize, Default)]
 struct TimelineItem {
+    id: u64,
     wedding_id: u64,
-    time: String,
+    time: String, // "HH:MM" on the wedding day, or "YYYY-MM-DD HH:MM"
+    duration_minutes: u64,
+    description: String,
+    responsible: String,
+    status: String, // e.g., "pending", "in-progress", "delayed", "completed", "overdue"
+    started_at: Option<String>,
+    finished_at: Option<String>,
+    delay_minutes: u64, // How far the item has moved from its original time
+}
+
+// Record of a timeline item moved by a delay
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TimelineShift {
+    item_id: u64,
+    description: String,
+    responsible: String,
+    minutes: u64,
+    from_time: String,
+    to_time: String,
+    caused_by: u64, // The timeline item that was delayed
+    shifted_at: String,
+}
+
+// Notification to a booked vendor whose timeline items moved
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct VendorNotification {
+    id: u64,
+    vendor_id: u64,
+    item_id: u64,
+    message: String,
+    created_at: String,
+}
+
+// Overdue and soon-due work for a wedding
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct AttentionReport {
+    overdue_tasks: Vec<Task>,
+    overdue_timeline_items: Vec<TimelineItem>,
+    tasks_due_soon: Vec<Task>,
+}
+
+// What is happening now and next on the wedding day
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct LiveTimelineStatus {
+    now: String,
+    current: Vec<TimelineItem>,
+    next: Option<TimelineItem>,
+    minutes_behind: u64,
+    shifts: Vec<TimelineShift>,
+}
+
+// One timeline item on a run sheet
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RunSheetEntry {
+    item_id: u64,
+    start: String, // "YYYY-MM-DD HH:MM"
+    end: String,
+    duration_minutes: u64,
     description: String,
+    status: String,
+}
+
+// Day-of schedule for one vendor or helper
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RunSheet {
+    responsible: String,
+    vendor_id: Option<u64>, // Set when the responsible party is a booked vendor
+    wedding_date: String,
+    venue: String, // The booked venue, or the wedding location
+    location: String,
+    contacts: Vec<String>, // The couple and their planner
+    first_call: String,    // Start of the first item
+    entries: Vec<RunSheetEntry>,
+}
+
+// Two timeline items that overlap for the same responsible party
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TimelineConflict {
+    item_id: u64,
+    other_item_id: u64,
     responsible: String,
-    status: String, // e.g., "pending", "completed", "overdue"
+    message: String,
 }
 
 // Task Record
@@ -103,163 +335,640 @@ struct Task {
     assigned_to: String,
     status: String, // e.g "pending", "in-progress", "completed"
     budget: u64,
+    duration_days: u64,   // Days of work the task needs
+    depends_on: Vec<u64>, // Tasks that must be completed first
+    checklist: Option<ChecklistOrigin>,
+}
+
+// Where a task generated from a checklist template came from
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct ChecklistOrigin {
+    template_id: u64,
+    days_before: u64,
+    generated_deadline: String, // Tasks still on this deadline and pending are untouched
+}
+
+// Checklist Item, e.g. "Book the venue", 365 days before the wedding
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct ChecklistItem {
+    title: String,
+    description: String,
+    days_before: u64,
+    duration_days: u64,
+    assigned_to: String,
+}
+
+// Checklist Template, built in or defined by the canister's controllers
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct ChecklistTemplate {
+    id: u64,
+    name: String,
+    description: String,
+    items: Vec<ChecklistItem>,
+    built_in: bool,
+}
+
+// Earliest schedule of a task given its dependencies
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TaskSchedule {
+    task_id: u64,
+    title: String,
+    status: String,
+    duration_days: u64,
+    earliest_start: String, // "YYYY-MM-DD"
+    earliest_finish: String,
+    deadline: String,
+    on_critical_path: bool,
+}
+
+// Task that can't meet its deadline, or the wedding date, given its dependencies
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct InfeasibleTask {
+    task_id: u64,
+    title: String,
+    deadline: String,
+    earliest_finish: String,
+    days_late: u64,
+}
+
+// Critical path of open tasks to the wedding date
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct CriticalPathReport {
+    wedding_date: String,
+    critical_path: Vec<u64>, // Task IDs, first to last
+    finish_date: String,     // When the last open task can be done at the earliest
+    days_to_spare: i64,      // Days between that and the wedding; negative if it runs over
+    schedule: Vec<TaskSchedule>,
+    infeasible: Vec<InfeasibleTask>,
 }
 
 // Guest Details Record
 #[derive(CandidType, Clone, Serialize, Deserialize, Default)]
 struct Guest {
+    id: u64,
+    household_id: Option<u64>,
     name: String,
-    guest_email: String,
-    rsvp_status: String, // e.g "pending", "confirmed", "declined"
+    guest_email: String, // May be empty for household members without their own email
+    rsvp_status: String, // e.g "invited", "pending", "waitlisted", "confirmed", "declined", "no-response"
+    age_group: AgeGroup,
+    meal_choice: String,
     dietary_restrictions: String,
     plus_one: bool,
+    plus_one_name: Option<String>,
     table_assignment: TableAssignment,
+    event_rsvps: Vec<EventRsvp>,  // Sub-events the guest is invited to
+    principal: Option<Principal>, // Bound when the guest redeems their invitation
+    rsvp_history: Vec<RsvpResponse>,
+    meal_option_id: Option<u64>,
+    plus_one_meal_option_id: Option<u64>,
+    allergens: Vec<Allergen>,
+    check_in_token_hash: Option<String>, // One-time day-of check-in token, stored hashed
+    checked_in_at: Option<String>,
+    tags: Vec<String>,
+    side: GuestSide,
+    relationship: Relationship,
+    address: String,
+    erased: bool, // Personal data removed on request; counts are kept
 }
 
-// Registry Item Record
+// Snapshot of a guest's RSVP each time they respond or change their answer
 #[derive(CandidType, Clone, Serialize, Deserialize, Default)]
-struct RegistryItem {
-    name: String,
-    description: String,
-    price: u64,
-    status: String, // "available", "purchased"
-    purchased_by: String,
+struct RsvpResponse {
+    status: String,
+    plus_one: bool,
+    plus_one_name: Option<String>,
+    meal_choice: String,
+    dietary_restrictions: String,
+    responded_at: String,
 }
 
-// Wedding Record with all details
+// Wedding Sub-Event Record, e.g. rehearsal dinner, ceremony, reception, brunch
 #[derive(CandidType, Clone, Serialize, Deserialize, Default)]
-struct Wedding {
+struct WeddingEvent {
     id: u64,
-    couple_names: Vec<String>,
-    date: String,
-    budget: u64,
+    name: String,
+    time: String,
     location: String,
-    guest_count: u64,
-    vendors: Vec<VendorBooking>,
-    timeline: Vec<TimelineItem>,
-    tasks: Vec<Task>,
-    guest_list: Vec<Guest>,
-    registry: Vec<RegistryItem>,
-    status: String, // "planning", "upcoming", "completed"
+    capacity: u64,
 }
 
-// Implement Storable and BoundedStorable for all types
-impl Storable for Wedding {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
-        Cow::Owned(Encode!(self).unwrap())
-    }
+// Guest RSVP to a single sub-event
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct EventRsvp {
+    event_id: u64,
+    status: String, // "invited", "confirmed", "declined"
+    table_assignment: TableAssignment,
+}
 
-    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
-    }
+// Guest seated at a sub-event
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct EventAttendee {
+    guest_id: u64,
+    name: String,
+    plus_one_name: Option<String>,
+    table_assignment: TableAssignment,
 }
 
-impl BoundedStorable for Wedding {
-    const MAX_SIZE: u32 = 1024;
-    const IS_FIXED_SIZE: bool = false;
+// Count of guests sharing a meal choice or dietary restriction
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct DietaryCount {
+    label: String,
+    count: u64,
 }
 
-impl Storable for Vendor {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
-        Cow::Owned(Encode!(self).unwrap())
-    }
+// Meal and dietary summary of confirmed attendees
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct DietarySummary {
+    meal_choices: Vec<DietaryCount>,
+    dietary_restrictions: Vec<DietaryCount>,
+}
 
-    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
-    }
+// Meal Option offered to guests, set by the couple or the booked caterer
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct MealOption {
+    id: u64,
+    name: String,
+    description: String,
+    allergens: Vec<Allergen>, // Allergens the dish contains
+    offered_by: Option<u64>,  // Catering vendor ID, if the caterer added it
 }
 
-impl BoundedStorable for Vendor {
-    const MAX_SIZE: u32 = 1024;
-    const IS_FIXED_SIZE: bool = false;
+// Meal and allergen counts for one table
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TableCateringCount {
+    table_assignment: TableAssignment,
+    meals: Vec<DietaryCount>,
+    allergens: Vec<DietaryCount>,
 }
 
-// Thread local storage
-thread_local! {
-    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
-        MemoryManager::init(DefaultMemoryImpl::default())
-    );
+// Catering Report of confirmed attendees, for the couple and their caterer
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct CateringReport {
+    total_attendees: u64,
+    meals: Vec<DietaryCount>,
+    meals_not_chosen: u64,
+    allergens: Vec<DietaryCount>,
+    dietary_notes: Vec<DietaryCount>, // Free-text dietary restrictions
+    tables: Vec<TableCateringCount>,  // Empty until guests are seated
+    lock_date: Option<String>,
+    locked: bool,
+}
 
-    static ID_COUNTER: RefCell<IdCell> = RefCell::new(
-        IdCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0))), 0)
-            .expect("Cannot create a counter")
-    );
+// Household Record, several guests sharing a single invitation
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct Household {
+    id: u64,
+    name: String,
+    contact_email: String,
+    max_party_size: u64,
+    responded: bool,
+    principal: Option<Principal>, // Bound when the household redeems its invitation
+}
 
-    static VENDOR_STORAGE: RefCell<StableBTreeMap<u64, Vendor, Memory>> = RefCell::new(
-        StableBTreeMap::init(
-            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(1))))
-    );
+// Invitation Record; the code itself is only stored as a hash
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct Invitation {
+    id: u64,
+    code_hash: String,
+    guest_id: Option<u64>,
+    household_id: Option<u64>,
+    issued_at: String,
+    revoked: bool,
+    redeemed_by: Option<Principal>,
+}
 
+// Headcount computed from individual attendees
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct Headcount {
+    invited: u64,
+    pending: u64,
+    waitlisted: u64,
+    confirmed: u64,
+    declined: u64,
+    confirmed_adults: u64,
+    confirmed_teens: u64,
+    confirmed_children: u64,
+    confirmed_infants: u64,
+}
 
-    static WEDDING_STORAGE: RefCell<StableBTreeMap<u64, Wedding, Memory>> = RefCell::new(
-        StableBTreeMap::init(
-            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2))))
-    );
+// RSVP Reminder queued for a guest who hasn't responded yet
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RsvpReminder {
+    id: u64,
+    guest_id: u64,
+    guest_name: String,
+    guest_email: String,
+    rsvp_deadline: String,
+    days_before: u64,
+    message: String,
+    queued_at: String,
 }
 
-/**
- * Payload Definitions
- */
+// Waitlist Entry for a guest approved while the wedding was full
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct WaitlistEntry {
+    guest_id: u64,
+    priority: u32, // Set by the couple; higher priorities are promoted first
+    table_assignment: TableAssignment,
+    added_at: String,
+}
 
-// Vendor Management Payload
-#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
-struct RegisterVendorPayload {
+// A guest's place on the waitlist
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct WaitlistPosition {
+    guest_id: u64,
     name: String,
-    category: Category,
-    description: String,
-    service_cost: u64,
-    availability: Vec<String>,
-    portfolio: Vec<String>,
+    position: u64,
+    priority: u32,
+    seats_needed: u64,
 }
 
-// Wedding Planning Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct CreateWeddingPayload {
-    couple_names: Vec<String>,
-    date: String,
-    budget: u64,
-    location: String,
-    guest_count: u64,
+// Arrivals at one table against the number expected
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TableCheckIn {
+    table_assignment: TableAssignment,
+    expected: u64,
+    checked_in: u64,
 }
 
-// GuestRSVP Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct GuestRsvpPayload {
-    wedding_id: u64,
-    name: String,
-    guest_email: String,
-    dietary_restrictions: String,
-    plus_one: bool,
+// Live check-in counts for the wedding day
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct CheckInSummary {
+    expected: u64,
+    checked_in: u64,
+    tables: Vec<TableCheckIn>,
 }
 
-// Approve RSVP Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct ApproveRsvpPayload {
-    wedding_id: u64,
-    guest_email: String,
-    table_assignment: TableAssignment,
+// Two guest records that may be the same person
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct PossibleDuplicate {
+    guest_id: u64,
+    guest_name: String,
+    other_guest_id: u64,
+    other_guest_name: String,
+    reason: String,
 }
 
-// Vendor Booking
-#[derive(CandidType, Serialize, Deserialize)]
-struct VendorBookingPayload {
-    vendor_id: u64,
-    wedding_id: u64,
-    wedding_offer: u64,
-    additional_details: Option<String>,
+// A page of guests matching a filter
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct GuestPage {
+    guests: Vec<Guest>,
+    total: u64, // Matching guests across all pages
+    offset: u64,
+    limit: u64,
 }
 
-// selectVendorForService Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct SelectVendorForServicePayload {
-    wedding_id: u64,
-    vendor_id: u64,
-    category: Category,
+// Headcount for one tag, side or relationship
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct GroupHeadcount {
+    label: String,
+    headcount: Headcount,
 }
 
-// Timeline Item Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct TimelineItemPayload {
+// Guest headcounts grouped by tag, side and relationship
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct GuestBreakdown {
+    tags: Vec<GroupHeadcount>,
+    sides: Vec<GroupHeadcount>,
+    relationships: Vec<GroupHeadcount>,
+}
+
+// A guest list row that could not be imported
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct ImportRowError {
+    row: u64, // 1-based, not counting the CSV header
+    name: String,
+    message: String,
+}
+
+// Outcome of importing one chunk of a guest list
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct ImportReport {
+    imported: u64,
+    duplicates: u64,
+    errors: Vec<ImportRowError>, // Includes duplicates
+}
+
+// A guest list row, as read from CSV or JSON
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct GuestImportRow {
+    name: String,
+    email: String,
+    age_group: String,
+    side: String,
+    relationship: String,
+    tags: Vec<String>,
+    address: String,
+    dietary_restrictions: String,
+    plus_one: bool,
+    plus_one_name: String,
+}
+
+// A guest list row, as written to CSV or JSON
+#[derive(Serialize, Deserialize, Default)]
+struct GuestExportRow {
+    name: String,
+    email: String,
+    rsvp_status: String,
+    plus_one: bool,
+    plus_one_name: String,
+    meal_choice: String,
+    meal_option: String,
+    dietary_restrictions: String,
+    allergens: Vec<String>,
+    table: String,
+    age_group: String,
+    side: String,
+    relationship: String,
+    tags: Vec<String>,
+    address: String,
+}
+
+// Registry Contribution Record
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RegistryContribution {
+    id: u64,
+    contributor: String,
+    amount: u64,
+    message: String,
+    date: String,
+    experience_id: Option<u64>, // Fund experience the contribution is earmarked for
+    thanked: bool,
+}
+
+// Fund Experience Record, e.g. "Dinner in Rome"
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct FundExperience {
+    id: u64,
+    name: String,
+    amount: u64,
+    amount_funded: u64,
+}
+
+// Registry Purchase Record
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RegistryPurchase {
+    id: u64,
+    purchased_by: String,
+    quantity: u64,
+    date: String,
+}
+
+// Registry Item Record
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RegistryItem {
+    id: u64,
+    kind: RegistryItemKind,
+    name: String,
+    description: String,
+    price: u64, // Price per unit, or the target amount of a fund
+    quantity_desired: u64,
+    quantity_received: u64, // Units bought outright; contributions are tracked in amount_funded
+    external_url: Option<String>,
+    sku: Option<String>,
+    priority: RegistryPriority,
+    category: RegistryCategory,
+    status: String, // "available", "reserved", "funding", "fulfilled", "purchased"
+    purchased_by: String, // Most recent purchaser
+    purchases: Vec<RegistryPurchase>,
+    amount_funded: u64, // Sum of all contributions, never above the price of the unbought units
+    contributions: Vec<RegistryContribution>,
+    reserved_by: Option<Principal>,
+    reserved_until: Option<u64>,      // IC time in nanoseconds
+    experiences: Vec<FundExperience>, // Only used by funds
+}
+
+// Fund Progress Record
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct FundProgress {
+    item_id: u64,
+    name: String,
+    target_amount: u64,
+    amount_raised: u64,
+    contributor_count: u64,
+    experiences: Vec<FundExperience>,
+    contributions: Vec<RegistryContribution>,
+}
+
+// Contribution awaiting a thank-you from the couple
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct PendingThankYou {
+    item_id: u64,
+    item_name: String,
+    contribution: RegistryContribution,
+}
+
+// Gift Ledger Entry
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct Gift {
+    id: u64,
+    giver: String,
+    guest_email: Option<String>, // Linked guest, when the giver is on the guest list
+    description: String,
+    amount: u64,
+    source: GiftSource,
+    registry_item_id: Option<u64>,
+    source_record_id: Option<u64>, // Registry purchase or contribution ID
+    gift_date: String,
+    thank_you_status: ThankYouStatus,
+    thanked_date: Option<String>,
+}
+
+// Room Type in a hotel room block
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RoomType {
+    id: u64,
+    name: String,      // e.g. "Double", "King Suite"
+    capacity: u64,     // People per room
+    count: u64,        // Rooms held in the block
+    nightly_rate: u64, // Group rate per night
+}
+
+// Room claimed in a block, shared by one or more guests
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RoomClaim {
+    id: u64,
+    room_type_id: u64,
+    occupants: Vec<u64>,      // Guest IDs; each guest brings their plus one
+    invited_guests: Vec<u64>, // Guests an occupant has agreed to share the room with
+    claimed_at: String,
+}
+
+// Hotel Room Block held for the wedding's guests
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RoomBlock {
+    id: u64,
+    property: String,
+    address: String,
+    room_types: Vec<RoomType>,
+    booking_cutoff: String, // "YYYY-MM-DD"; guests can't claim rooms after this date
+    booking_code: String,   // Group code guests quote to the hotel
+    claims: Vec<RoomClaim>,
+}
+
+// Claimed and unclaimed rooms of one type
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RoomTypeAvailability {
+    room_type_id: u64,
+    name: String,
+    capacity: u64,
+    nightly_rate: u64,
+    claimed: u64,
+    unclaimed: u64,
+    occupants: u64, // People staying, plus ones included
+    rooms: Vec<RoomClaim>,
+}
+
+// Claimed and unclaimed rooms in one block
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct RoomBlockAvailability {
+    block_id: u64,
+    property: String,
+    booking_cutoff: String,
+    room_types: Vec<RoomTypeAvailability>,
+}
+
+// Attending guest who hasn't claimed a room yet
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct GuestWithoutLodging {
+    guest_id: u64,
+    name: String,
+    rsvp_status: String,
+    headcount: u64,
+}
+
+// Lodging Report for the couple
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct LodgingReport {
+    blocks: Vec<RoomBlockAvailability>,
+    guests_without_lodging: Vec<GuestWithoutLodging>,
+}
+
+// Transport Run, e.g. a shuttle from the hotel to the ceremony
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TransportRun {
+    id: u64,
+    route: String, // e.g. "Hotel to ceremony"
+    departure_time: String,
+    vehicle_capacity: u64,
+    vendor_id: u64,       // The booked Transport vendor running it
+    passengers: Vec<u64>, // Guest IDs; each guest brings their plus one
+}
+
+// Passenger on a transport run
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TransportPassenger {
+    guest_id: u64,
+    name: String,
+    plus_one_name: Option<String>,
+    household_id: Option<u64>,
+}
+
+// Passenger manifest of one transport run, for the transport vendor
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct TransportManifest {
+    run_id: u64,
+    route: String,
+    departure_time: String,
+    vehicle_capacity: u64,
+    seats_taken: u64,
+    passengers: Vec<TransportPassenger>,
+}
+
+// Wedding Record with all details
+#[derive(CandidType, Clone, Serialize, Deserialize)]
+struct Wedding {
+    id: u64,
+    owner: Principal, // The couple's principal
+    couple_names: Vec<String>,
+    date: String,
+    budget: u64,
+    location: String,
+    guest_count: u64,
+    vendors: Vec<VendorBooking>,
+    timeline: Vec<TimelineItem>,
+    timeline_shifts: Vec<TimelineShift>,
+    vendor_notifications: Vec<VendorNotification>,
+    tasks: Vec<Task>,
+    guest_list: Vec<Guest>,
+    households: Vec<Household>,
+    events: Vec<WeddingEvent>,
+    invitations: Vec<Invitation>,
+    code_salt: String, // Random per-wedding key mixed into code and token hashes
+    waitlist: Vec<WaitlistEntry>, // In the order guests were added
+    registry: Vec<RegistryItem>,
+    status: String,                // "planning", "upcoming", "completed"
+    rsvp_deadline: Option<String>, // "YYYY-MM-DD"; guests can't respond after this date
+    rsvp_reminder_days: Vec<u64>,  // Days before the deadline to remind pending guests
+    rsvp_deadline_action: RsvpDeadlineAction,
+    rsvps_closed: bool, // Set once the deadline action has been applied
+    rsvp_reminders: Vec<RsvpReminder>,
+    meal_options: Vec<MealOption>,
+    catering_lock_date: Option<String>, // "YYYY-MM-DD"; meals and RSVPs are final after this date
+    check_in_staff: Vec<Principal>,     // Venue staff allowed to check guests in
+    planners: Vec<Principal>,           // Planners who can see guest contact details
+    room_blocks: Vec<RoomBlock>,
+    transport_runs: Vec<TransportRun>,
+    // Hide who bought what until after the wedding
+    hide_registry_purchasers: bool,
+    gifts: Vec<Gift>,
+}
+
+// Implement Storable and BoundedStorable for all types
+impl Storable for Wedding {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+}
+
+impl BoundedStorable for Wedding {
+    // Weddings carry their guest list (a few hundred guests), households and registry
+    const MAX_SIZE: u32 = 256 * 1024;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl Storable for ChecklistTemplate {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+}
+
+impl BoundedStorable for ChecklistTemplate {
+    const MAX_SIZE: u32 = 16 * 1024;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl Storable for Vendor {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+}
+
+impl BoundedStorable for Vendor {
+    const MAX_SIZE: u32 = 1024;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+/**
+ * Legacy Types
+ */
+
+// Timeline Item as stored before timeline items had IDs
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct LegacyTimelineItem {
     wedding_id: u64,
     time: String,
     description: String,
@@ -267,141 +976,8326 @@ struct TimelineItemPayload {
     status: String,
 }
 
-// Task Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct TaskPayload {
-    wedding_id: u64,
+// Task as stored before dependencies and durations
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct LegacyTask {
+    id: u64,
     title: String,
     description: String,
     deadline: String,
     assigned_to: String,
-    budget: u64,
-}
-
-// Update Task Status Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct UpdateTaskStatusPayload {
-    wedding_id: u64,
-    task_id: u64,
     status: String,
+    budget: u64,
 }
 
-// Delete Task Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct DeleteTaskPayload {
-    wedding_id: u64,
-    task_id: u64,
+// Guest as stored before guests had IDs
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct LegacyGuest {
+    name: String,
+    guest_email: String,
+    rsvp_status: String,
+    dietary_restrictions: String,
+    plus_one: bool,
+    table_assignment: TableAssignment,
 }
 
-// Add Registry Item Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct AddRegistryItemPayload {
-    wedding_id: u64,
+// Registry Item as stored before registry items had IDs and quantities
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct LegacyRegistryItem {
     name: String,
     description: String,
     price: u64,
+    status: String,
+    purchased_by: String,
 }
 
-// Update Registry Item Status Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct UpdateRegistryItemStatusPayload {
-    wedding_id: u64,
-    item_name: String,
+// Wedding as stored in the original 1 KiB wedding storage
+#[derive(CandidType, Clone, Serialize, Deserialize, Default)]
+struct LegacyWedding {
+    id: u64,
+    couple_names: Vec<String>,
+    date: String,
+    budget: u64,
+    location: String,
+    guest_count: u64,
+    vendors: Vec<VendorBooking>,
+    timeline: Vec<LegacyTimelineItem>,
+    tasks: Vec<LegacyTask>,
+    guest_list: Vec<LegacyGuest>,
+    registry: Vec<LegacyRegistryItem>,
     status: String,
-    purchased_by: String,
 }
 
-// Delete Registry Item Payload
-#[derive(CandidType, Serialize, Deserialize)]
-struct DeleteRegistryItemPayload {
-    wedding_id: u64,
-    item_name: String,
+impl Storable for LegacyWedding {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
 }
 
-// Result type for error handling
-#[derive(CandidType, Serialize, Deserialize)]
-enum Message {
-    Success(String),
-    Error(String),
-    InvalidInput(String),
-    VendorNotFound(String),
-    WeddingNotFound(String),
-    NoTimeLineItemsFound(String),
-    DateUnavailable(String),
-    UnauthorizedAction(String),
-    BudgetExceeded(String),
-    InvalidDate(String),
+impl BoundedStorable for LegacyWedding {
+    const MAX_SIZE: u32 = 1024;
+    const IS_FIXED_SIZE: bool = false;
 }
 
-/**
- * Helper Functions
- */
+// Thread local storage
+thread_local! {
+    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
+        MemoryManager::init(DefaultMemoryImpl::default())
+    );
+
+    static ID_COUNTER: RefCell<IdCell> = RefCell::new(
+        IdCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0))), 0)
+            .expect("Cannot create a counter")
+    );
+
+    static VENDOR_STORAGE: RefCell<StableBTreeMap<u64, Vendor, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(1))))
+    );
+
+
+    // Original wedding storage; emptied into WEDDING_STORAGE on upgrade
+    static LEGACY_WEDDING_STORAGE: RefCell<StableBTreeMap<u64, LegacyWedding, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2))))
+    );
+
+    static WEDDING_STORAGE: RefCell<StableBTreeMap<u64, Wedding, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(4))))
+    );
+
+    static TEMPLATE_STORAGE: RefCell<StableBTreeMap<u64, ChecklistTemplate, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3))))
+    );
+
+    // Pending RSVP timers per wedding; timers don't survive upgrades and are set again
+    static RSVP_TIMERS: RefCell<HashMap<u64, Vec<TimerId>>> = RefCell::new(HashMap::new());
+
+    // Wedding ID the next overdue sweep starts from
+    static OVERDUE_SWEEP_CURSOR: RefCell<u64> = const { RefCell::new(0) };
+}
+
+/**
+ * Payload Definitions
+ */
+
+// Vendor Management Payload
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct RegisterVendorPayload {
+    name: String,
+    category: Category,
+    description: String,
+    service_cost: u64,
+    availability: Vec<String>,
+    portfolio: Vec<String>,
+}
+
+// Wedding Planning Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct CreateWeddingPayload {
+    couple_names: Vec<String>,
+    date: String,
+    budget: u64,
+    location: String,
+    guest_count: u64,
+    checklist_template_id: Option<u64>, // Defaults to the standard built-in checklist
+}
+
+// Invite Guest Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct InviteGuestPayload {
+    wedding_id: u64,
+    name: String,
+    guest_email: String,
+}
+
+// Issue Invitation Payload, for either a guest or a household
+#[derive(CandidType, Serialize, Deserialize)]
+struct IssueInvitationPayload {
+    wedding_id: u64,
+    guest_id: Option<u64>,
+    household_id: Option<u64>,
+}
+
+// GuestRSVP Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct GuestRsvpPayload {
+    wedding_id: u64,
+    invitation_code: String,
+    name: String,
+    guest_email: String,
+    meal_choice: String,
+    dietary_restrictions: String,
+    plus_one: bool,
+    plus_one_name: Option<String>,
+    room: Option<RoomRequest>, // Optionally claim a room in a block
+}
+
+// Update RSVP Payload
+// `guest_id` takes precedence; it is needed for household members without an email
+#[derive(CandidType, Serialize, Deserialize)]
+struct UpdateRsvpPayload {
+    wedding_id: u64,
+    guest_id: Option<u64>,
+    guest_email: String,
+    meal_choice: String,
+    dietary_restrictions: String,
+    plus_one: bool,
+    plus_one_name: Option<String>,
+    room: Option<RoomRequest>, // Optionally claim or change a room in a block
+}
+
+// Room Request; set `room_id` to share a room another guest already claimed
+#[derive(CandidType, Serialize, Deserialize)]
+struct RoomRequest {
+    block_id: u64,
+    room_type_id: u64,
+    room_id: Option<u64>,
+}
+
+// Claim Room Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct ClaimRoomPayload {
+    wedding_id: u64,
+    guest_id: u64,
+    room: RoomRequest,
+}
+
+// Room Type Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct RoomTypePayload {
+    name: String,
+    capacity: u64,
+    count: u64,
+    nightly_rate: u64,
+}
+
+// Add Room Block Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AddRoomBlockPayload {
+    wedding_id: u64,
+    property: String,
+    address: String,
+    room_types: Vec<RoomTypePayload>,
+    booking_cutoff: String,
+    booking_code: String,
+}
+
+// Add Transport Run Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AddTransportRunPayload {
+    wedding_id: u64,
+    route: String,
+    departure_time: String,
+    vehicle_capacity: u64,
+    vendor_id: u64,
+}
+
+// Assign Transport Payload; a household puts all its attending members on the run
+#[derive(CandidType, Serialize, Deserialize)]
+struct AssignTransportPayload {
+    wedding_id: u64,
+    run_id: u64,
+    guest_ids: Vec<u64>,
+    household_id: Option<u64>,
+}
+
+// Add Meal Option Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AddMealOptionPayload {
+    wedding_id: u64,
+    name: String,
+    description: String,
+    allergens: Vec<Allergen>,
+}
+
+// Guest Meal Selection Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct SetGuestMealPayload {
+    wedding_id: u64,
+    guest_id: u64,
+    meal_option_id: Option<u64>,
+    plus_one_meal_option_id: Option<u64>,
+    allergens: Vec<Allergen>,
+}
+
+// Update Guest Profile Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct UpdateGuestProfilePayload {
+    wedding_id: u64,
+    guest_id: u64,
+    tags: Vec<String>,
+    side: GuestSide,
+    relationship: Relationship,
+    age_group: AgeGroup,
+    address: String,
+}
+
+// Guest Filter Payload; every field left empty matches all guests
+#[derive(CandidType, Serialize, Deserialize)]
+struct GuestFilterPayload {
+    wedding_id: u64,
+    tag: Option<String>,
+    side: Option<GuestSide>,
+    relationship: Option<Relationship>,
+    age_group: Option<AgeGroup>,
+    rsvp_status: Option<String>,
+    table_assignment: Option<TableAssignment>,
+    search: Option<String>, // Matches name, email or address
+    offset: u64,
+    limit: u64, // At most MAX_GUEST_PAGE_SIZE
+}
+
+// Import Guests Payload; large lists can be sent in several chunks
+// Each CSV chunk starts with a header row, e.g. name,email,side,relationship,tags,...
+#[derive(CandidType, Serialize, Deserialize)]
+struct ImportGuestsPayload {
+    wedding_id: u64,
+    format: GuestListFormat,
+    data: String,
+}
+
+// Set RSVP Deadline Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct SetRsvpRemindersPayload {
+    wedding_id: u64,
+    reminder_days: Vec<u64>, // e.g. [14, 7, 1] days before the deadline
+    deadline_action: RsvpDeadlineAction,
+}
+
+// Decline or Withdraw RSVP Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct RsvpChangePayload {
+    wedding_id: u64,
+    guest_id: Option<u64>,
+    guest_email: String,
+}
+
+// Approve RSVP Payload
+// `guest_id` takes precedence; it is needed for household members without an email
+#[derive(CandidType, Serialize, Deserialize)]
+struct ApproveRsvpPayload {
+    wedding_id: u64,
+    guest_id: Option<u64>,
+    guest_email: String,
+    table_assignment: TableAssignment,
+}
+
+// Create Wedding Event Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct CreateWeddingEventPayload {
+    wedding_id: u64,
+    name: String,
+    time: String,
+    location: String,
+    capacity: u64,
+}
+
+// Invite Guests To Event Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct InviteGuestsToEventPayload {
+    wedding_id: u64,
+    event_id: u64,
+    guest_ids: Vec<u64>,
+}
+
+// Event RSVP Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct EventRsvpPayload {
+    wedding_id: u64,
+    event_id: u64,
+    guest_id: Option<u64>,
+    guest_email: String,
+    attending: bool,
+}
+
+// Assign Event Table Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AssignEventTablePayload {
+    wedding_id: u64,
+    event_id: u64,
+    guest_id: u64,
+    table_assignment: TableAssignment,
+}
+
+// Household Attendee Payload
+#[derive(CandidType, Serialize, Deserialize, Clone)]
+struct HouseholdAttendeePayload {
+    name: String,
+    guest_email: String,
+    age_group: AgeGroup,
+    meal_choice: String,
+    dietary_restrictions: String,
+}
+
+// Create Household Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct CreateHouseholdPayload {
+    wedding_id: u64,
+    name: String,
+    contact_email: String,
+    max_party_size: u64,
+    attendees: Vec<HouseholdAttendeePayload>,
+}
+
+// Household Attendee RSVP Payload
+// Leave `guest_id` empty to add a named attendee the couple didn't list
+#[derive(CandidType, Serialize, Deserialize, Clone)]
+struct HouseholdAttendeeRsvpPayload {
+    guest_id: Option<u64>,
+    name: String,
+    age_group: AgeGroup,
+    attending: bool,
+    meal_choice: String,
+    dietary_restrictions: String,
+}
+
+// Household RSVP Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct HouseholdRsvpPayload {
+    wedding_id: u64,
+    household_id: u64,
+    invitation_code: Option<String>, // Not needed once the household's principal is bound
+    attendees: Vec<HouseholdAttendeeRsvpPayload>,
+}
+
+// Vendor Booking
+#[derive(CandidType, Serialize, Deserialize)]
+struct VendorBookingPayload {
+    vendor_id: u64,
+    wedding_id: u64,
+    wedding_offer: u64,
+    additional_details: Option<String>,
+}
+
+// selectVendorForService Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct SelectVendorForServicePayload {
+    wedding_id: u64,
+    vendor_id: u64,
+    category: Category,
+}
+
+// Timeline Item Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct TimelineItemPayload {
+    wedding_id: u64,
+    time: String,
+    duration_minutes: u64,
+    description: String,
+    responsible: String,
+    status: String,
+}
+
+// Update Timeline Item Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct UpdateTimelineItemPayload {
+    wedding_id: u64,
+    item_id: u64,
+    time: String,
+    duration_minutes: u64,
+    description: String,
+    responsible: String,
+    status: String,
+}
+
+// Task Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct TaskPayload {
+    wedding_id: u64,
+    title: String,
+    description: String,
+    deadline: String,
+    assigned_to: String,
+    budget: u64,
+    duration_days: u64,
+    depends_on: Vec<u64>,
+}
+
+// Update Task Status Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct UpdateTaskStatusPayload {
+    wedding_id: u64,
+    task_id: u64,
+    status: String,
+}
+
+// Checklist Template Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct ChecklistTemplatePayload {
+    name: String,
+    description: String,
+    items: Vec<ChecklistItem>,
+}
+
+// Set Task Dependencies Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct SetTaskDependenciesPayload {
+    wedding_id: u64,
+    task_id: u64,
+    depends_on: Vec<u64>,
+}
+
+// Delete Task Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct DeleteTaskPayload {
+    wedding_id: u64,
+    task_id: u64,
+}
+
+// Add Registry Item Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AddRegistryItemPayload {
+    wedding_id: u64,
+    name: String,
+    description: String,
+    price: u64,
+    quantity_desired: u64,
+    external_url: Option<String>,
+    sku: Option<String>,
+    priority: RegistryPriority,
+    category: RegistryCategory,
+}
+
+// Fund Experience Payload
+#[derive(CandidType, Serialize, Deserialize, Clone)]
+struct FundExperiencePayload {
+    name: String,
+    amount: u64,
+}
+
+// Add Registry Fund Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AddRegistryFundPayload {
+    wedding_id: u64,
+    name: String,
+    description: String,
+    target_amount: u64,
+    priority: RegistryPriority,
+    category: RegistryCategory,
+    experiences: Vec<FundExperiencePayload>,
+}
+
+// Add Fund Experience Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AddFundExperiencePayload {
+    wedding_id: u64,
+    item_id: u64,
+    name: String,
+    amount: u64,
+}
+
+// Add Off-Registry Gift Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct AddGiftPayload {
+    wedding_id: u64,
+    giver: String,
+    guest_email: Option<String>,
+    description: String,
+    amount: u64,
+    gift_date: String,
+}
+
+// Update Gift Thank-you Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct UpdateGiftThankYouPayload {
+    wedding_id: u64,
+    gift_id: u64,
+    thank_you_status: ThankYouStatus,
+    thanked_date: Option<String>, // Defaults to today when the note is sent
+    guest_email: Option<String>,  // Re-link the gift to a different guest
+}
+
+// Update Registry Item Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct UpdateRegistryItemPayload {
+    wedding_id: u64,
+    item_id: u64,
+    name: String,
+    description: String,
+    price: u64,
+    quantity_desired: u64,
+    external_url: Option<String>,
+    sku: Option<String>,
+    priority: RegistryPriority,
+    category: RegistryCategory,
+}
+
+// Update Registry Item Status Payload
+// `item_id` takes precedence; `item_name` is kept for older clients
+#[derive(CandidType, Serialize, Deserialize)]
+struct UpdateRegistryItemStatusPayload {
+    wedding_id: u64,
+    item_id: Option<u64>,
+    item_name: String,
+    status: String,
+    purchased_by: String,
+}
+
+// Contribute To Registry Item Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct ContributeToRegistryItemPayload {
+    wedding_id: u64,
+    item_id: Option<u64>,
+    item_name: String,
+    contributor: String,
+    amount: u64,
+    message: String,
+    experience_id: Option<u64>,
+}
+
+// Reserve Registry Item Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct ReserveRegistryItemPayload {
+    wedding_id: u64,
+    item_id: Option<u64>,
+    item_name: String,
+}
+
+// Confirm Registry Purchase Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct ConfirmRegistryPurchasePayload {
+    wedding_id: u64,
+    item_id: Option<u64>,
+    item_name: String,
+    purchased_by: String,
+}
+
+// Delete Registry Item Payload
+#[derive(CandidType, Serialize, Deserialize)]
+struct DeleteRegistryItemPayload {
+    wedding_id: u64,
+    item_id: Option<u64>,
+    item_name: String,
+}
+
+// Result type for error handling
+#[derive(CandidType, Serialize, Deserialize)]
+enum Message {
+    Success(String),
+    Error(String),
+    InvalidInput(String),
+    VendorNotFound(String),
+    WeddingNotFound(String),
+    NoTimeLineItemsFound(String),
+    DateUnavailable(String),
+    UnauthorizedAction(String),
+    BudgetExceeded(String),
+    InvalidDate(String),
+}
+
+/**
+ * Helper Functions
+ */
+
+// Generate UUID
+fn generate_uuid() -> u64 {
+    let id = ID_COUNTER.with(|counter| {
+        let current_id = *counter.borrow().get(); // Use the binding to get the current ID
+        let _ = counter.borrow_mut().set(current_id + 1); // Now you can safely mutate the counter
+        current_id // Return the current_id
+    });
+
+    id
+}
+
+// Current date and time, based on the IC system time
+fn current_datetime() -> chrono::NaiveDateTime {
+    let nanos = ic_cdk::api::time();
+    chrono::DateTime::from_timestamp(
+        (nanos / 1_000_000_000) as i64,
+        (nanos % 1_000_000_000) as u32,
+    )
+    .unwrap_or_default()
+    .naive_utc()
+}
+
+// Current date and time as a string
+fn current_timestamp() -> String {
+    current_datetime().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+// Parse a "YYYY-MM-DD" date string
+fn parse_date(date: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
+// When a timeline item ends, or None if its time can't be read or the end is out of range
+fn timeline_item_end(wedding: &Wedding, item: &TimelineItem) -> Option<chrono::NaiveDateTime> {
+    let start = parse_timeline_time(wedding, &item.time)?;
+    let minutes = i64::try_from(item.duration_minutes).ok()?;
+    start.checked_add_signed(chrono::Duration::try_minutes(minutes)?)
+}
+
+// Parse the date part of a gift date, which may also carry a "HH:MM:SS" time
+fn parse_gift_date(date: &str) -> Option<chrono::NaiveDate> {
+    date.trim().get(..10).and_then(parse_date)
+}
+
+// Parse a timeline time, "HH:MM" on the wedding day or "YYYY-MM-DD HH:MM"
+fn parse_timeline_time(wedding: &Wedding, time: &str) -> Option<chrono::NaiveDateTime> {
+    let time = time.trim();
+    if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M") {
+        return Some(datetime);
+    }
+    let time = chrono::NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+    parse_date(&wedding.date).map(|date| date.and_time(time))
+}
+
+// Check if the wedding date is in the past
+fn wedding_has_passed(wedding: &Wedding) -> bool {
+    match parse_date(&wedding.date) {
+        Some(date) => date < current_datetime().date(),
+        None => false,
+    }
+}
+
+// Check if the caller is the couple that created the wedding
+fn is_wedding_owner(wedding: &Wedding) -> bool {
+    wedding.owner == caller()
+}
+
+// Find a guest by ID, falling back to an email lookup
+fn find_guest(wedding: &Wedding, guest_id: Option<u64>, guest_email: &str) -> Option<usize> {
+    match guest_id {
+        Some(guest_id) => wedding
+            .guest_list
+            .iter()
+            .position(|guest| guest.id == guest_id),
+        None if guest_email.is_empty() => None,
+        None => wedding
+            .guest_list
+            .iter()
+            .position(|guest| same_email(&guest.guest_email, guest_email)),
+    }
+}
+
+// Check if the caller can check guests in: the couple, venue staff, or the booked venue or planner
+fn can_check_in_guests(wedding: &Wedding) -> bool {
+    can_view_guest_details(wedding)
+        || wedding.check_in_staff.contains(&caller())
+        || caller_booked_vendor(wedding, Category::Venue).is_some()
+}
+
+// Check if the caller is one of the wedding's planners, or its booked planning vendor
+fn is_wedding_planner(wedding: &Wedding) -> bool {
+    wedding.planners.contains(&caller())
+        || caller_booked_vendor(wedding, Category::Planning).is_some()
+}
+
+// Guest contact details are only visible to the couple and their planners
+fn can_view_guest_details(wedding: &Wedding) -> bool {
+    is_wedding_owner(wedding) || is_wedding_planner(wedding)
+}
+
+// A guest as the caller may see them: in full for the couple, planners and the guest
+fn guest_view(wedding: &Wedding, guest: &Guest) -> Guest {
+    if can_view_guest_details(wedding) || can_act_for_guest(wedding, guest) {
+        // Token hashes never leave the canister, not even for the couple
+        let mut guest = guest.clone();
+        guest.check_in_token_hash = None;
+        return guest;
+    }
+    Guest {
+        id: guest.id,
+        household_id: guest.household_id,
+        name: guest.name.clone(),
+        rsvp_status: guest.rsvp_status.clone(),
+        age_group: guest.age_group.clone(),
+        plus_one: guest.plus_one,
+        table_assignment: guest.table_assignment.clone(),
+        event_rsvps: guest.event_rsvps.clone(),
+        erased: guest.erased,
+        ..Default::default()
+    }
+}
+
+// A wedding as the caller may see it, with other guests' personal data redacted
+fn wedding_view(wedding: Wedding) -> Wedding {
+    let mut wedding = redact_registry_purchasers(wedding);
+    let guests: Vec<Guest> = wedding
+        .guest_list
+        .iter()
+        .map(|guest| guest_view(&wedding, guest))
+        .collect();
+    wedding.guest_list = guests;
+    wedding.code_salt = "".to_string();
+    for invitation in wedding.invitations.iter_mut() {
+        invitation.code_hash = "".to_string();
+    }
+    if can_view_guest_details(&wedding) {
+        return wedding;
+    }
+
+    for household in wedding.households.iter_mut() {
+        if household.principal != Some(caller()) {
+            household.contact_email = "".to_string();
+        }
+    }
+    for invitation in wedding.invitations.iter_mut() {
+        invitation.redeemed_by = None;
+    }
+    wedding.room_blocks = room_blocks_view(&wedding);
+    wedding.rsvp_reminders.clear();
+    wedding.gifts.clear();
+    wedding
+}
+
+// Remove a guest's personal data. Meal choices, dietary restrictions and allergens stay, unlinked
+// from any name, so headcounts and catering totals don't change
+fn scrub_guest(guest: &mut Guest) {
+    guest.name = "Erased guest".to_string();
+    guest.guest_email = "".to_string();
+    guest.plus_one_name = None;
+    guest.address = "".to_string();
+    guest.tags.clear();
+    guest.principal = None;
+    guest.check_in_token_hash = None;
+    for response in guest.rsvp_history.iter_mut() {
+        response.meal_choice = "".to_string();
+        response.dietary_restrictions = "".to_string();
+        response.plus_one_name = None;
+    }
+    guest.erased = true;
+}
+
+// Remove a person's name or email from gifts and the registry
+fn scrub_gift_records(wedding: &mut Wedding, name: &str, email: &str) {
+    let matches = |value: &str| {
+        let value = value.trim();
+        !value.is_empty() && (value.eq_ignore_ascii_case(name.trim()) || same_email(value, email))
+    };
+
+    for gift in wedding.gifts.iter_mut() {
+        if matches(&gift.giver) || gift.guest_email.as_deref().is_some_and(matches) {
+            gift.giver = "".to_string();
+            gift.guest_email = None;
+        }
+    }
+    for item in wedding.registry.iter_mut() {
+        if matches(&item.purchased_by) {
+            item.purchased_by = "".to_string();
+        }
+        for purchase in item.purchases.iter_mut() {
+            if matches(&purchase.purchased_by) {
+                purchase.purchased_by = "".to_string();
+            }
+        }
+        for contribution in item.contributions.iter_mut() {
+            if matches(&contribution.contributor) {
+                contribution.contributor = "".to_string();
+            }
+        }
+    }
+}
+
+// Hash an invitation code or check-in token for storage and lookup, keyed with the wedding's salt
+fn hash_code(salt: &str, code: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(code.trim().to_uppercase().as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Give a wedding its code salt the first time a code or token is issued
+fn ensure_code_salt(wedding: &mut Wedding, random_bytes: &[u8]) {
+    if wedding.code_salt.is_empty() {
+        wedding.code_salt = random_bytes
+            .iter()
+            .rev()
+            .take(16)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+    }
+}
+
+// An invitation as returned to the couple, without its code hash
+fn invitation_view(mut invitation: Invitation) -> Invitation {
+    invitation.code_hash = "".to_string();
+    invitation
+}
+
+// Turn random bytes into a readable code, skipping look-alike characters
+fn random_code(random_bytes: &[u8], length: usize) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+    random_bytes
+        .iter()
+        .take(length)
+        .map(|byte| ALPHABET[*byte as usize % ALPHABET.len()] as char)
+        .collect()
+}
+
+// Redeem an invitation code, binding the redeemer to the invited guest or household
+fn redeem_invitation(
+    wedding: &mut Wedding,
+    code: &str,
+    redeemer: Principal,
+) -> Result<Invitation, Message> {
+    // Every unauthenticated client shares the anonymous principal, so it can't be bound
+    if redeemer == Principal::anonymous() {
+        return Err(Message::UnauthorizedAction(
+            "Sign in before redeeming an invitation code".to_string(),
+        ));
+    }
+
+    let code_hash = hash_code(&wedding.code_salt, code);
+    let invitation = wedding
+        .invitations
+        .iter_mut()
+        .find(|invitation| invitation.code_hash == code_hash && !invitation.revoked);
+
+    let invitation = match invitation {
+        Some(invitation) => invitation,
+        None => {
+            return Err(Message::UnauthorizedAction(
+                "Invalid or revoked invitation code".to_string(),
+            ));
+        }
+    };
+
+    if invitation
+        .redeemed_by
+        .is_some_and(|principal| principal != redeemer)
+    {
+        return Err(Message::UnauthorizedAction(
+            "This invitation has already been redeemed".to_string(),
+        ));
+    }
+    invitation.redeemed_by = Some(redeemer);
+    let invitation = invitation.clone();
+
+    if let Some(guest_id) = invitation.guest_id {
+        for guest in wedding.guest_list.iter_mut() {
+            if guest.id == guest_id {
+                guest.principal = Some(redeemer);
+            }
+        }
+    }
+    if let Some(household_id) = invitation.household_id {
+        for household in wedding.households.iter_mut() {
+            if household.id == household_id {
+                household.principal = Some(redeemer);
+            }
+        }
+    }
+
+    Ok(invitation)
+}
+
+// Check if the caller may act for a guest: their bound principal, or the couple
+fn can_act_for_guest(wedding: &Wedding, guest: &Guest) -> bool {
+    if is_wedding_owner(wedding) || guest.principal == Some(caller()) {
+        return true;
+    }
+    guest.household_id.is_some_and(|household_id| {
+        wedding
+            .households
+            .iter()
+            .any(|household| household.id == household_id && household.principal == Some(caller()))
+    })
+}
+
+// Check if the RSVP deadline has passed
+fn rsvp_deadline_passed(wedding: &Wedding) -> bool {
+    match wedding.rsvp_deadline.as_deref().and_then(parse_date) {
+        Some(deadline) => deadline < current_datetime().date(),
+        None => false,
+    }
+}
+
+// Guests can respond until the deadline or the catering lock. The couple is exempt from
+// both: approving, merging and removing guests stay open to them after the lock
+fn check_rsvp_open(wedding: &Wedding) -> Result<(), Message> {
+    if is_wedding_owner(wedding) {
+        return Ok(());
+    }
+    if catering_locked(wedding) {
+        return Err(Message::InvalidDate(
+            "The final headcount has been locked; please contact the couple".to_string(),
+        ));
+    }
+    if rsvp_deadline_passed(wedding) {
+        return Err(Message::InvalidDate(
+            "The RSVP deadline has passed; please contact the couple".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Check if the catering lock date has passed and numbers are final
+fn catering_locked(wedding: &Wedding) -> bool {
+    match wedding.catering_lock_date.as_deref().and_then(parse_date) {
+        Some(lock_date) => lock_date < current_datetime().date(),
+        None => false,
+    }
+}
+
+// ID of the caller's vendor in the given category, if it is booked for the wedding
+fn caller_booked_vendor(wedding: &Wedding, category: Category) -> Option<u64> {
+    wedding
+        .vendors
+        .iter()
+        .filter(|booking| booking.status == "confirmed" || booking.status == "paid")
+        .find(|booking| {
+            VENDOR_STORAGE.with(|storage| {
+                storage
+                    .borrow()
+                    .get(&booking.vendor_id)
+                    .is_some_and(|vendor| vendor.category == category && vendor.owner == caller())
+            })
+        })
+        .map(|booking| booking.vendor_id)
+}
+
+// Add a snapshot of the guest's current answer to their RSVP history
+fn record_rsvp_response(guest: &mut Guest) {
+    guest.rsvp_history.push(RsvpResponse {
+        status: guest.rsvp_status.clone(),
+        plus_one: guest.plus_one,
+        plus_one_name: guest.plus_one_name.clone(),
+        meal_choice: guest.meal_choice.clone(),
+        dietary_restrictions: guest.dietary_restrictions.clone(),
+        responded_at: current_timestamp(),
+    });
+}
+
+// Waitlist in promotion order: highest priority first, then first come first served
+fn waitlist_order(wedding: &Wedding) -> Vec<WaitlistEntry> {
+    let mut waitlist = wedding.waitlist.clone();
+    waitlist.sort_by_key(|entry| std::cmp::Reverse(entry.priority));
+    waitlist
+}
+
+// A guest's position on the waitlist, starting at 1
+fn waitlist_position(wedding: &Wedding, guest_id: u64) -> Option<WaitlistPosition> {
+    let waitlist = waitlist_order(wedding);
+    let position = waitlist
+        .iter()
+        .position(|entry| entry.guest_id == guest_id)?;
+    let entry = &waitlist[position];
+    let guest = wedding
+        .guest_list
+        .iter()
+        .find(|guest| guest.id == guest_id)?;
+
+    Some(WaitlistPosition {
+        guest_id,
+        name: guest.name.clone(),
+        position: position as u64 + 1,
+        priority: entry.priority,
+        seats_needed: guest_headcount(guest),
+    })
+}
+
+// Give seats back to the wedding and promote waitlisted guests who now fit
+fn release_seats(wedding: &mut Wedding, seats: u64) {
+    wedding.guest_count += seats;
+
+    // Drop entries for guests who declined, withdrew or were removed
+    let guests = wedding.guest_list.clone();
+    wedding.waitlist.retain(|entry| {
+        guests
+            .iter()
+            .any(|guest| guest.id == entry.guest_id && guest.rsvp_status == "waitlisted")
+    });
+
+    // Once the catering numbers are final, only the couple promotes guests, through approve_rsvp
+    if catering_locked(wedding) {
+        return;
+    }
+
+    for entry in waitlist_order(wedding) {
+        let guest = match wedding
+            .guest_list
+            .iter_mut()
+            .find(|guest| guest.id == entry.guest_id)
+        {
+            Some(guest) => guest,
+            None => continue,
+        };
+
+        let seats_needed = guest_headcount(guest);
+        if seats_needed > wedding.guest_count {
+            continue;
+        }
+
+        guest.rsvp_status = "confirmed".to_string();
+        guest.table_assignment = entry.table_assignment.clone();
+        wedding.guest_count -= seats_needed;
+        wedding
+            .waitlist
+            .retain(|waiting| waiting.guest_id != entry.guest_id);
+    }
+}
+
+// Number of seats a guest takes, including their plus one
+fn guest_headcount(guest: &Guest) -> u64 {
+    if guest.plus_one {
+        2
+    } else {
+        1
+    }
+}
+
+// Basic shape check for an email address
+fn is_valid_email(email: &str) -> bool {
+    let (local, domain) = match email.split_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !email.chars().any(char::is_whitespace)
+}
+
+// Emails are compared trimmed and lowercased
+fn normalise_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+// Check if two emails belong to the same guest; empty emails never match
+fn same_email(a: &str, b: &str) -> bool {
+    let a = normalise_email(a);
+    !a.is_empty() && a == normalise_email(b)
+}
+
+// Normalise an optional email, rejecting malformed ones
+fn validate_email(email: &str) -> Result<String, Message> {
+    let email = normalise_email(email);
+    if !email.is_empty() && !is_valid_email(&email) {
+        return Err(Message::InvalidInput(format!(
+            "Invalid email address: {}",
+            email
+        )));
+    }
+    Ok(email)
+}
+
+// Name split into lowercase words without punctuation
+fn name_words(name: &str) -> Vec<String> {
+    name.split_whitespace()
+        .map(normalise_label)
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+// Number of single-character edits between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Why two guests look like the same person, if they do
+fn duplicate_reason(guest: &Guest, other: &Guest) -> Option<String> {
+    if same_email(&guest.guest_email, &other.guest_email) {
+        return Some("Same email".to_string());
+    }
+
+    let words = name_words(&guest.name);
+    let other_words = name_words(&other.name);
+    if words.is_empty() || other_words.is_empty() {
+        return None;
+    }
+
+    let mut sorted = words.clone();
+    let mut other_sorted = other_words.clone();
+    sorted.sort();
+    other_sorted.sort();
+    if sorted == other_sorted {
+        return Some("Same name".to_string());
+    }
+
+    // "A Smith" and "Ann Smith"
+    let (first, last) = (&words[0], &words[words.len() - 1]);
+    let (other_first, other_last) = (&other_words[0], &other_words[other_words.len() - 1]);
+    if words.len() > 1
+        && other_words.len() > 1
+        && last == other_last
+        && (first.len() == 1 || other_first.len() == 1)
+        && first.chars().next() == other_first.chars().next()
+    {
+        return Some("Same surname and initial".to_string());
+    }
+
+    // Small typos in longer names, e.g. "Jonathan Smith" and "Johnathan Smith". Short words
+    // allow one edit, so "Ann Smith" and "Ben Smith" aren't taken for the same person
+    let joined = words.concat();
+    let other_joined = other_words.concat();
+    let similar_words = words.len() != other_words.len()
+        || words.iter().zip(other_words.iter()).all(|(word, other)| {
+            let allowed = if word.len().min(other.len()) < 5 {
+                1
+            } else {
+                2
+            };
+            edit_distance(word, other) <= allowed
+        });
+    if joined.len().min(other_joined.len()) >= 6
+        && similar_words
+        && edit_distance(&joined, &other_joined) <= 2
+    {
+        return Some("Similar name".to_string());
+    }
+
+    None
+}
+
+// How far along an RSVP is, used to keep the more advanced answer when merging
+fn rsvp_status_rank(status: &str) -> u8 {
+    match status {
+        "confirmed" => 5,
+        "waitlisted" => 4,
+        "pending" => 3,
+        "declined" => 2,
+        "no-response" => 1,
+        _ => 0,
+    }
+}
+
+// Combine two guest records, preferring `keep` and filling its gaps from `other`
+fn merge_guest_records(keep: &Guest, other: &Guest) -> Guest {
+    fn fill(value: &mut String, other: &str) {
+        if value.trim().is_empty() {
+            *value = other.to_string();
+        }
+    }
+
+    let mut merged = keep.clone();
+    fill(&mut merged.guest_email, &other.guest_email);
+    fill(&mut merged.meal_choice, &other.meal_choice);
+    fill(
+        &mut merged.dietary_restrictions,
+        &other.dietary_restrictions,
+    );
+    fill(&mut merged.address, &other.address);
+    if other.name.len() > merged.name.len() {
+        merged.name = other.name.clone();
+    }
+
+    if rsvp_status_rank(&other.rsvp_status) > rsvp_status_rank(&merged.rsvp_status) {
+        merged.rsvp_status = other.rsvp_status.clone();
+        merged.plus_one = other.plus_one;
+        merged.plus_one_name = other.plus_one_name.clone();
+        merged.plus_one_meal_option_id = other.plus_one_meal_option_id;
+    }
+    if merged.table_assignment == TableAssignment::Unassigned {
+        merged.table_assignment = other.table_assignment.clone();
+    }
+
+    merged.household_id = merged.household_id.or(other.household_id);
+    merged.principal = merged.principal.or(other.principal);
+    merged.meal_option_id = merged.meal_option_id.or(other.meal_option_id);
+    merged.check_in_token_hash = merged
+        .check_in_token_hash
+        .clone()
+        .or(other.check_in_token_hash.clone());
+    merged.checked_in_at = merged.checked_in_at.clone().or(other.checked_in_at.clone());
+
+    for tag in other.tags.iter() {
+        if !merged
+            .tags
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(tag))
+        {
+            merged.tags.push(tag.clone());
+        }
+    }
+    for allergen in other.allergens.iter() {
+        if !merged.allergens.contains(allergen) {
+            merged.allergens.push(allergen.clone());
+        }
+    }
+    for rsvp in other.event_rsvps.iter() {
+        if !merged
+            .event_rsvps
+            .iter()
+            .any(|existing| existing.event_id == rsvp.event_id)
+        {
+            merged.event_rsvps.push(rsvp.clone());
+        }
+    }
+    merged
+        .rsvp_history
+        .extend(other.rsvp_history.iter().cloned());
+    merged
+        .rsvp_history
+        .sort_by(|a, b| a.responded_at.cmp(&b.responded_at));
+
+    merged
+}
+
+// Lowercase a label and drop spaces and punctuation, e.g. "Partner A" -> "partnera"
+fn normalise_label(label: &str) -> String {
+    label
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+// Split CSV text into records, handling quoted fields
+fn parse_csv(data: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    // Skip blank lines
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+    records
+}
+
+// Quote a CSV field if it needs it. Guest-supplied values that a spreadsheet would read
+// as a formula get a leading apostrophe
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+// Read guest list rows from CSV or JSON
+fn read_guest_rows(format: &GuestListFormat, data: &str) -> Result<Vec<GuestImportRow>, Message> {
+    match format {
+        GuestListFormat::Json => serde_json::from_str(data)
+            .map_err(|error| Message::InvalidInput(format!("Invalid JSON guest list: {}", error))),
+        GuestListFormat::Csv => {
+            let mut records = parse_csv(data).into_iter();
+            let header: Vec<String> = match records.next() {
+                Some(header) => header
+                    .iter()
+                    .map(|column| normalise_label(column))
+                    .collect(),
+                None => return Ok(Vec::new()),
+            };
+            if !header.iter().any(|column| column == "name") {
+                return Err(Message::InvalidInput(
+                    "The CSV header must include a name column".to_string(),
+                ));
+            }
+
+            Ok(records
+                .map(|record| {
+                    let column = |name: &str| {
+                        header
+                            .iter()
+                            .position(|column| column == name)
+                            .and_then(|index| record.get(index))
+                            .map(|value| value.trim().to_string())
+                            .unwrap_or_default()
+                    };
+                    GuestImportRow {
+                        name: column("name"),
+                        email: column("email"),
+                        age_group: column("agegroup"),
+                        side: column("side"),
+                        relationship: column("relationship"),
+                        tags: column("tags")
+                            .split(';')
+                            .map(|tag| tag.trim().to_string())
+                            .filter(|tag| !tag.is_empty())
+                            .collect(),
+                        address: column("address"),
+                        dietary_restrictions: column("dietaryrestrictions"),
+                        plus_one: matches!(
+                            normalise_label(&column("plusone")).as_str(),
+                            "true" | "yes" | "y" | "1"
+                        ),
+                        plus_one_name: column("plusonename"),
+                    }
+                })
+                .collect())
+        }
+    }
+}
+
+// Turn an imported row into an invited guest
+fn guest_from_import_row(row: &GuestImportRow) -> Result<Guest, String> {
+    let name = row.name.trim();
+    if name.is_empty() {
+        return Err("Guest name is required".to_string());
+    }
+
+    let email = normalise_email(&row.email);
+    if !email.is_empty() && !is_valid_email(&email) {
+        return Err(format!("Invalid email address: {}", email));
+    }
+
+    let age_group = match normalise_label(&row.age_group).as_str() {
+        "" | "adult" => AgeGroup::Adult,
+        "teen" => AgeGroup::Teen,
+        "child" => AgeGroup::Child,
+        "infant" => AgeGroup::Infant,
+        other => return Err(format!("Unknown age group: {}", other)),
+    };
+    let side = match normalise_label(&row.side).as_str() {
+        "" | "mutual" => GuestSide::Mutual,
+        "partnera" | "a" => GuestSide::PartnerA,
+        "partnerb" | "b" => GuestSide::PartnerB,
+        other => return Err(format!("Unknown side: {}", other)),
+    };
+    let relationship = match normalise_label(&row.relationship).as_str() {
+        "" | "other" => Relationship::Other,
+        "family" => Relationship::Family,
+        "friend" => Relationship::Friend,
+        "colleague" => Relationship::Colleague,
+        other => return Err(format!("Unknown relationship: {}", other)),
+    };
+
+    let plus_one_name = Some(row.plus_one_name.trim().to_string()).filter(|name| !name.is_empty());
+    if row.plus_one && plus_one_name.is_none() {
+        return Err("Plus one name is required".to_string());
+    }
+
+    Ok(Guest {
+        id: generate_uuid(),
+        household_id: None,
+        name: name.to_string(),
+        guest_email: email,
+        rsvp_status: "invited".to_string(),
+        age_group,
+        meal_choice: "".to_string(),
+        dietary_restrictions: row.dietary_restrictions.trim().to_string(),
+        plus_one: row.plus_one,
+        plus_one_name: if row.plus_one { plus_one_name } else { None },
+        table_assignment: TableAssignment::Unassigned,
+        event_rsvps: Vec::new(),
+        principal: None,
+        rsvp_history: Vec::new(),
+        meal_option_id: None,
+        plus_one_meal_option_id: None,
+        allergens: Vec::new(),
+        check_in_token_hash: None,
+        checked_in_at: None,
+        tags: row.tags.clone(),
+        side,
+        relationship,
+        address: row.address.trim().to_string(),
+        erased: false,
+    })
+}
+
+// Check if a guest matches every field set in a guest filter
+fn guest_matches_filter(guest: &Guest, filter: &GuestFilterPayload) -> bool {
+    let search = filter
+        .search
+        .as_deref()
+        .map(|search| search.trim().to_lowercase())
+        .filter(|search| !search.is_empty());
+
+    filter.tag.as_deref().is_none_or(|tag| {
+        guest
+            .tags
+            .iter()
+            .any(|guest_tag| guest_tag.eq_ignore_ascii_case(tag.trim()))
+    }) && filter.side.as_ref().is_none_or(|side| guest.side == *side)
+        && filter
+            .relationship
+            .as_ref()
+            .is_none_or(|relationship| guest.relationship == *relationship)
+        && filter
+            .age_group
+            .as_ref()
+            .is_none_or(|age_group| guest.age_group == *age_group)
+        && filter
+            .rsvp_status
+            .as_deref()
+            .is_none_or(|status| guest.rsvp_status == status)
+        && filter
+            .table_assignment
+            .as_ref()
+            .is_none_or(|table| guest.table_assignment == *table)
+        && search.is_none_or(|search| {
+            [&guest.name, &guest.guest_email, &guest.address]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&search))
+        })
+}
+
+// Tally guests by RSVP status into a headcount of individual attendees
+fn tally_headcount<'a>(guests: impl Iterator<Item = (&'a Guest, &'a str)>) -> Headcount {
+    let mut headcount = Headcount::default();
+    for (guest, status) in guests {
+        let people = guest_headcount(guest);
+        match status {
+            "invited" => headcount.invited += people,
+            "pending" => headcount.pending += people,
+            "waitlisted" => headcount.waitlisted += people,
+            "declined" => headcount.declined += people,
+            "confirmed" => {
+                headcount.confirmed += people;
+                match guest.age_group {
+                    AgeGroup::Adult => headcount.confirmed_adults += 1,
+                    AgeGroup::Teen => headcount.confirmed_teens += 1,
+                    AgeGroup::Child => headcount.confirmed_children += 1,
+                    AgeGroup::Infant => headcount.confirmed_infants += 1,
+                }
+                // Plus ones are counted as adults
+                if guest.plus_one {
+                    headcount.confirmed_adults += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    headcount
+}
+
+// Add one to the count for a label, ignoring case
+fn add_count(counts: &mut Vec<DietaryCount>, label: &str) {
+    let label = label.trim();
+    if label.is_empty() {
+        return;
+    }
+    match counts
+        .iter_mut()
+        .find(|count| count.label.eq_ignore_ascii_case(label))
+    {
+        Some(count) => count.count += 1,
+        None => counts.push(DietaryCount {
+            label: label.to_string(),
+            count: 1,
+        }),
+    }
+}
+
+// Summarise meal choices and dietary restrictions of the given guests
+fn summarise_diets<'a>(guests: impl Iterator<Item = &'a Guest>) -> DietarySummary {
+    let mut summary = DietarySummary::default();
+    for guest in guests {
+        add_count(&mut summary.meal_choices, &guest.meal_choice);
+        add_count(
+            &mut summary.dietary_restrictions,
+            &guest.dietary_restrictions,
+        );
+    }
+    summary
+}
+
+// Build the catering report from confirmed attendees
+fn build_catering_report(wedding: &Wedding) -> CateringReport {
+    let meal_name = |meal_option_id: Option<u64>| {
+        meal_option_id.and_then(|id| {
+            wedding
+                .meal_options
+                .iter()
+                .find(|option| option.id == id)
+                .map(|option| option.name.clone())
+        })
+    };
+
+    let mut report = CateringReport {
+        lock_date: wedding.catering_lock_date.clone(),
+        locked: catering_locked(wedding),
+        ..Default::default()
+    };
+
+    for guest in wedding
+        .guest_list
+        .iter()
+        .filter(|guest| guest.rsvp_status == "confirmed")
+    {
+        let mut meals = vec![meal_name(guest.meal_option_id)];
+        if guest.plus_one {
+            meals.push(meal_name(guest.plus_one_meal_option_id));
+        }
+        let allergens: Vec<String> = guest
+            .allergens
+            .iter()
+            .map(|allergen| format!("{:?}", allergen))
+            .collect();
+
+        report.total_attendees += meals.len() as u64;
+        add_count(&mut report.dietary_notes, &guest.dietary_restrictions);
+
+        let table_index = if guest.table_assignment == TableAssignment::Unassigned {
+            None
+        } else {
+            match report
+                .tables
+                .iter()
+                .position(|table| table.table_assignment == guest.table_assignment)
+            {
+                Some(index) => Some(index),
+                None => {
+                    report.tables.push(TableCateringCount {
+                        table_assignment: guest.table_assignment.clone(),
+                        ..Default::default()
+                    });
+                    Some(report.tables.len() - 1)
+                }
+            }
+        };
+
+        for meal in meals.iter() {
+            match meal {
+                Some(meal) => {
+                    add_count(&mut report.meals, meal);
+                    if let Some(index) = table_index {
+                        add_count(&mut report.tables[index].meals, meal);
+                    }
+                }
+                None => report.meals_not_chosen += 1,
+            }
+        }
+        for allergen in allergens.iter() {
+            add_count(&mut report.allergens, allergen);
+            if let Some(index) = table_index {
+                add_count(&mut report.tables[index].allergens, allergen);
+            }
+        }
+    }
+
+    report
+}
+
+// Number of people staying in a claimed room
+fn room_occupancy(wedding: &Wedding, room: &RoomClaim) -> u64 {
+    wedding
+        .guest_list
+        .iter()
+        .filter(|guest| room.occupants.contains(&guest.id))
+        .map(guest_headcount)
+        .sum()
+}
+
+// Whether a guest has claimed, or shares, a room in any block
+fn has_lodging(wedding: &Wedding, guest_id: u64) -> bool {
+    wedding
+        .room_blocks
+        .iter()
+        .flat_map(|block| block.claims.iter())
+        .any(|room| room.occupants.contains(&guest_id))
+}
+
+// Take a guest out of their room; rooms left empty go back to the block
+fn vacate_room(wedding: &mut Wedding, guest_id: u64) {
+    for block in wedding.room_blocks.iter_mut() {
+        for room in block.claims.iter_mut() {
+            room.occupants.retain(|occupant| *occupant != guest_id);
+        }
+        block.claims.retain(|room| !room.occupants.is_empty());
+    }
+}
+
+// Room blocks as the caller may see them: who shares which room is only shown to the couple
+// and planners, and to guests for their own room
+fn room_blocks_view(wedding: &Wedding) -> Vec<RoomBlock> {
+    let mut blocks = wedding.room_blocks.clone();
+    if can_view_guest_details(wedding) {
+        return blocks;
+    }
+    for block in blocks.iter_mut() {
+        block.claims.retain(|room| {
+            wedding.guest_list.iter().any(|guest| {
+                room.occupants.contains(&guest.id) && can_act_for_guest(wedding, guest)
+            })
+        });
+    }
+    blocks
+}
+
+// Give back the rooms of guests who were removed or are no longer coming
+fn release_unused_rooms(wedding: &mut Wedding) {
+    let not_coming: Vec<u64> = wedding
+        .room_blocks
+        .iter()
+        .flat_map(|block| block.claims.iter())
+        .flat_map(|room| room.occupants.iter().copied())
+        .filter(|guest_id| {
+            !wedding.guest_list.iter().any(|guest| {
+                guest.id == *guest_id
+                    && guest.rsvp_status != "declined"
+                    && guest.rsvp_status != "no-response"
+            })
+        })
+        .collect();
+    for guest_id in not_coming {
+        vacate_room(wedding, guest_id);
+    }
+}
+
+// Claim a room, or a place in a room another guest claimed, for a guest
+fn claim_room_for(
+    wedding: &mut Wedding,
+    guest_id: u64,
+    request: &RoomRequest,
+) -> Result<RoomClaim, Message> {
+    let guest = match wedding.guest_list.iter().find(|guest| guest.id == guest_id) {
+        Some(guest) => guest.clone(),
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+    if guest.rsvp_status == "invited"
+        || guest.rsvp_status == "declined"
+        || guest.rsvp_status == "no-response"
+    {
+        return Err(Message::Error(
+            "Only guests who are attending can claim a room".to_string(),
+        ));
+    }
+
+    let block_index = match wedding
+        .room_blocks
+        .iter()
+        .position(|block| block.id == request.block_id)
+    {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Room block not found".to_string()));
+        }
+    };
+
+    let cutoff_passed = parse_date(&wedding.room_blocks[block_index].booking_cutoff)
+        .is_some_and(|cutoff| cutoff < current_datetime().date());
+    if cutoff_passed && !is_wedding_owner(wedding) {
+        return Err(Message::InvalidDate(format!(
+            "Rooms in this block could be claimed until {}; please contact the hotel",
+            wedding.room_blocks[block_index].booking_cutoff
+        )));
+    }
+
+    let room_type = match wedding.room_blocks[block_index]
+        .room_types
+        .iter()
+        .find(|room_type| room_type.id == request.room_type_id)
+    {
+        Some(room_type) => room_type.clone(),
+        None => {
+            return Err(Message::Error("Room type not found".to_string()));
+        }
+    };
+
+    // Moving rooms frees the guest's current place first
+    vacate_room(wedding, guest_id);
+    let headcount = guest_headcount(&guest);
+
+    match request.room_id {
+        Some(room_id) => {
+            let room = wedding.room_blocks[block_index]
+                .claims
+                .iter()
+                .find(|room| room.id == room_id && room.room_type_id == room_type.id)
+                .cloned();
+            let room = match room {
+                Some(room) => room,
+                None => {
+                    return Err(Message::Error("Room not found".to_string()));
+                }
+            };
+            if !room.invited_guests.contains(&guest_id) && !is_wedding_owner(wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Someone staying in this room has to invite this guest first".to_string(),
+                ));
+            }
+            if room_occupancy(wedding, &room) + headcount > room_type.capacity {
+                return Err(Message::InvalidInput(format!(
+                    "This room sleeps at most {}",
+                    room_type.capacity
+                )));
+            }
+
+            let room = wedding.room_blocks[block_index]
+                .claims
+                .iter_mut()
+                .find(|claim| claim.id == room.id)
+                .expect("room exists");
+            room.occupants.push(guest_id);
+            room.invited_guests.retain(|invited| *invited != guest_id);
+            Ok(room.clone())
+        }
+        None => {
+            let claimed = wedding.room_blocks[block_index]
+                .claims
+                .iter()
+                .filter(|room| room.room_type_id == room_type.id)
+                .count() as u64;
+            if claimed >= room_type.count {
+                return Err(Message::Error(format!(
+                    "No {} rooms are left in this block",
+                    room_type.name
+                )));
+            }
+            if headcount > room_type.capacity {
+                return Err(Message::InvalidInput(format!(
+                    "This room sleeps at most {}",
+                    room_type.capacity
+                )));
+            }
+
+            let room = RoomClaim {
+                id: generate_uuid(),
+                room_type_id: room_type.id,
+                occupants: vec![guest_id],
+                invited_guests: Vec::new(),
+                claimed_at: current_timestamp(),
+            };
+            wedding.room_blocks[block_index].claims.push(room.clone());
+            Ok(room)
+        }
+    }
+}
+
+// Check a guest's room still has space for them, e.g. after adding a plus one
+fn check_room_capacity(wedding: &Wedding, guest_id: u64) -> Result<(), Message> {
+    for block in wedding.room_blocks.iter() {
+        for room in block.claims.iter() {
+            if !room.occupants.contains(&guest_id) {
+                continue;
+            }
+            let capacity = block
+                .room_types
+                .iter()
+                .find(|room_type| room_type.id == room.room_type_id)
+                .map_or(0, |room_type| room_type.capacity);
+            if room_occupancy(wedding, room) > capacity {
+                return Err(Message::InvalidInput(format!(
+                    "Your room sleeps at most {}; please choose another room",
+                    capacity
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Seats taken on a transport run
+fn transport_seats_taken(wedding: &Wedding, run: &TransportRun) -> u64 {
+    wedding
+        .guest_list
+        .iter()
+        .filter(|guest| run.passengers.contains(&guest.id))
+        .map(guest_headcount)
+        .sum()
+}
+
+// Take guests who were removed or are no longer coming off their transport runs
+fn release_unused_transport(wedding: &mut Wedding) {
+    let coming: Vec<u64> = wedding
+        .guest_list
+        .iter()
+        .filter(|guest| guest.rsvp_status != "declined" && guest.rsvp_status != "no-response")
+        .map(|guest| guest.id)
+        .collect();
+    for run in wedding.transport_runs.iter_mut() {
+        run.passengers.retain(|guest_id| coming.contains(guest_id));
+    }
+}
+
+// Check a guest's transport runs still have seats for them, e.g. after adding a plus one
+fn check_transport_capacity(wedding: &Wedding, guest_id: u64) -> Result<(), Message> {
+    let full_run = wedding.transport_runs.iter().find(|run| {
+        run.passengers.contains(&guest_id)
+            && transport_seats_taken(wedding, run) > run.vehicle_capacity
+    });
+    match full_run {
+        Some(run) => Err(Message::InvalidInput(format!(
+            "The {} transport has no seats left for a plus one",
+            run.route
+        ))),
+        None => Ok(()),
+    }
+}
+
+// Passenger manifest of a transport run
+fn build_transport_manifest(wedding: &Wedding, run: &TransportRun) -> TransportManifest {
+    TransportManifest {
+        run_id: run.id,
+        route: run.route.clone(),
+        departure_time: run.departure_time.clone(),
+        vehicle_capacity: run.vehicle_capacity,
+        seats_taken: transport_seats_taken(wedding, run),
+        passengers: wedding
+            .guest_list
+            .iter()
+            .filter(|guest| run.passengers.contains(&guest.id))
+            .map(|guest| TransportPassenger {
+                guest_id: guest.id,
+                name: guest.name.clone(),
+                plus_one_name: guest.plus_one_name.clone(),
+                household_id: guest.household_id,
+            })
+            .collect(),
+    }
+}
+
+// Claimed and unclaimed rooms, and attending guests who still need a room
+fn build_lodging_report(wedding: &Wedding) -> LodgingReport {
+    let blocks = wedding
+        .room_blocks
+        .iter()
+        .map(|block| RoomBlockAvailability {
+            block_id: block.id,
+            property: block.property.clone(),
+            booking_cutoff: block.booking_cutoff.clone(),
+            room_types: block
+                .room_types
+                .iter()
+                .map(|room_type| {
+                    let rooms: Vec<RoomClaim> = block
+                        .claims
+                        .iter()
+                        .filter(|room| room.room_type_id == room_type.id)
+                        .cloned()
+                        .collect();
+                    let claimed = rooms.len() as u64;
+                    RoomTypeAvailability {
+                        room_type_id: room_type.id,
+                        name: room_type.name.clone(),
+                        capacity: room_type.capacity,
+                        nightly_rate: room_type.nightly_rate,
+                        claimed,
+                        unclaimed: room_type.count.saturating_sub(claimed),
+                        occupants: rooms.iter().map(|room| room_occupancy(wedding, room)).sum(),
+                        rooms,
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    let guests_without_lodging = wedding
+        .guest_list
+        .iter()
+        .filter(|guest| {
+            guest.rsvp_status == "pending"
+                || guest.rsvp_status == "confirmed"
+                || guest.rsvp_status == "waitlisted"
+        })
+        .filter(|guest| !has_lodging(wedding, guest.id))
+        .map(|guest| GuestWithoutLodging {
+            guest_id: guest.id,
+            name: guest.name.clone(),
+            rsvp_status: guest.rsvp_status.clone(),
+            headcount: guest_headcount(guest),
+        })
+        .collect();
+
+    LodgingReport {
+        blocks,
+        guests_without_lodging,
+    }
+}
+
+// Vendors booked for the wedding (confirmed or paid)
+fn booked_vendors(wedding: &Wedding) -> Vec<Vendor> {
+    wedding
+        .vendors
+        .iter()
+        .filter(|booking| booking.status == "confirmed" || booking.status == "paid")
+        .filter_map(|booking| {
+            VENDOR_STORAGE.with(|storage| storage.borrow().get(&booking.vendor_id))
+        })
+        .collect()
+}
+
+// Day-of schedule of the timeline items a vendor or helper is responsible for
+fn build_run_sheet(wedding: &Wedding, responsible: &str) -> RunSheet {
+    let vendors = booked_vendors(wedding);
+    let named = |category: Category| {
+        vendors
+            .iter()
+            .find(|vendor| vendor.category == category)
+            .map(|vendor| vendor.name.clone())
+    };
+
+    let mut contacts = vec![format!("Couple: {}", wedding.couple_names.join(" & "))];
+    if let Some(planner) = named(Category::Planning) {
+        contacts.push(format!("Planner: {}", planner));
+    }
+
+    let entries: Vec<RunSheetEntry> = sorted_timeline(wedding)
+        .iter()
+        .filter(|item| {
+            item.responsible
+                .trim()
+                .eq_ignore_ascii_case(responsible.trim())
+        })
+        .map(|item| {
+            let start = parse_timeline_time(wedding, &item.time);
+            let end = timeline_item_end(wedding, item);
+            RunSheetEntry {
+                item_id: item.id,
+                start: start
+                    .map(|start| start.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| item.time.clone()),
+                end: end
+                    .map(|end| end.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default(),
+                duration_minutes: item.duration_minutes,
+                description: item.description.clone(),
+                status: item.status.clone(),
+            }
+        })
+        .collect();
+
+    RunSheet {
+        responsible: responsible.trim().to_string(),
+        vendor_id: vendors
+            .iter()
+            .find(|vendor| vendor.name.trim().eq_ignore_ascii_case(responsible.trim()))
+            .map(|vendor| vendor.id),
+        wedding_date: wedding.date.clone(),
+        venue: named(Category::Venue).unwrap_or_else(|| wedding.location.clone()),
+        location: wedding.location.clone(),
+        contacts,
+        first_call: entries
+            .first()
+            .map(|entry| entry.start.clone())
+            .unwrap_or_default(),
+        entries,
+    }
+}
+
+// Run sheets the caller may see: all for the couple and planners, their own for a booked vendor
+fn visible_run_sheets(
+    wedding: &Wedding,
+    responsible: Option<String>,
+) -> Result<Vec<RunSheet>, Message> {
+    if is_wedding_owner(wedding) || is_wedding_planner(wedding) {
+        if let Some(responsible) = responsible {
+            return Ok(vec![build_run_sheet(wedding, &responsible)]);
+        }
+
+        // One sheet per responsible party, in order of their first item
+        let mut names: Vec<String> = Vec::new();
+        for item in sorted_timeline(wedding) {
+            let name = item.responsible.trim();
+            if !name.is_empty() && !names.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+                names.push(name.to_string());
+            }
+        }
+        return Ok(names
+            .iter()
+            .map(|name| build_run_sheet(wedding, name))
+            .collect());
+    }
+
+    let vendor = booked_vendors(wedding)
+        .into_iter()
+        .find(|vendor| vendor.owner == caller());
+    match vendor {
+        Some(vendor) => {
+            let other_party = responsible.is_some_and(|responsible| {
+                !responsible.trim().eq_ignore_ascii_case(vendor.name.trim())
+            });
+            if other_party {
+                return Err(Message::UnauthorizedAction(
+                    "Vendors can only see their own run sheet".to_string(),
+                ));
+            }
+            Ok(vec![build_run_sheet(wedding, &vendor.name)])
+        }
+        None => Err(Message::UnauthorizedAction(
+            "Only the couple, their planner or a booked vendor can see run sheets".to_string(),
+        )),
+    }
+}
+
+// Plain-text rendering of a run sheet, for printing
+fn render_run_sheet(sheet: &RunSheet) -> String {
+    let mut text = format!("RUN SHEET: {}\n", sheet.responsible);
+    text.push_str(&format!("Date: {}\n", sheet.wedding_date));
+    text.push_str(&format!("Venue: {}\n", sheet.venue));
+    if sheet.location != sheet.venue {
+        text.push_str(&format!("Location: {}\n", sheet.location));
+    }
+    for contact in sheet.contacts.iter() {
+        text.push_str(&format!("{}\n", contact));
+    }
+    text.push_str(&format!("First call: {}\n\n", sheet.first_call));
+
+    if sheet.entries.is_empty() {
+        text.push_str("No items scheduled\n");
+    }
+    for entry in sheet.entries.iter() {
+        let time = if entry.end.is_empty() || entry.duration_minutes == 0 {
+            entry.start.clone()
+        } else {
+            format!("{} - {}", entry.start, entry.end)
+        };
+        text.push_str(&format!(
+            "{}  {} [{}]\n",
+            time, entry.description, entry.status
+        ));
+    }
+    text
+}
+
+// Timeline in chronological order; items at the same time keep the couple's order
+fn sorted_timeline(wedding: &Wedding) -> Vec<TimelineItem> {
+    let mut timeline = wedding.timeline.clone();
+    timeline.sort_by_key(|item| {
+        let time = parse_timeline_time(wedding, &item.time);
+        (time.is_none(), time)
+    });
+    timeline
+}
+
+// Check if two timeline items take the same person at the same time
+fn timeline_items_overlap(wedding: &Wedding, item: &TimelineItem, other: &TimelineItem) -> bool {
+    let responsible = item.responsible.trim();
+    if item.id == other.id
+        || responsible.is_empty()
+        || !responsible.eq_ignore_ascii_case(other.responsible.trim())
+    {
+        return false;
+    }
+
+    let (start, end, other_start, other_end) = match (
+        parse_timeline_time(wedding, &item.time),
+        timeline_item_end(wedding, item),
+        parse_timeline_time(wedding, &other.time),
+        timeline_item_end(wedding, other),
+    ) {
+        (Some(start), Some(end), Some(other_start), Some(other_end)) => {
+            (start, end, other_start, other_end)
+        }
+        _ => return false,
+    };
+    start == other_start || (start < other_end && other_start < end)
+}
+
+// Timeline items that overlap with the given one for the same responsible party
+fn timeline_overlaps<'a>(wedding: &'a Wedding, item: &TimelineItem) -> Vec<&'a TimelineItem> {
+    wedding
+        .timeline
+        .iter()
+        .filter(|other| timeline_items_overlap(wedding, item, other))
+        .collect()
+}
+
+// Success message for a timeline change, warning about any overlaps
+fn timeline_change_message(wedding: &Wedding, item: &TimelineItem, action: &str) -> String {
+    let overlaps = timeline_overlaps(wedding, item);
+    if overlaps.is_empty() {
+        return format!("Timeline item {} successfully", action);
+    }
+    let descriptions: Vec<&str> = overlaps
+        .iter()
+        .map(|other| other.description.as_str())
+        .collect();
+    format!(
+        "Timeline item {} successfully, but {} is also needed for: {}",
+        action,
+        item.responsible.trim(),
+        descriptions.join(", ")
+    )
+}
+
+// Write a shifted timeline time in the same style as the original
+fn format_timeline_time(
+    wedding: &Wedding,
+    original: &str,
+    datetime: chrono::NaiveDateTime,
+) -> String {
+    let same_day = parse_date(&wedding.date) == Some(datetime.date());
+    if same_day && chrono::NaiveTime::parse_from_str(original.trim(), "%H:%M").is_ok() {
+        datetime.format("%H:%M").to_string()
+    } else {
+        datetime.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+// Check if the caller can mark a timeline item's progress: the couple, a planner or its vendor
+fn can_update_timeline_item(wedding: &Wedding, item: &TimelineItem) -> bool {
+    is_wedding_owner(wedding)
+        || is_wedding_planner(wedding)
+        || booked_vendors(wedding).iter().any(|vendor| {
+            vendor.owner == caller()
+                && vendor
+                    .name
+                    .trim()
+                    .eq_ignore_ascii_case(item.responsible.trim())
+        })
+}
+
+// Push a timeline item, and every item starting after it that hasn't started, back by some
+// minutes. Items of other responsible parties only move when `move_others` is set
+fn delay_timeline(
+    wedding: &mut Wedding,
+    item_id: u64,
+    minutes: u64,
+    move_others: bool,
+    shifted_at: &str,
+) -> Vec<TimelineShift> {
+    let (delayed_start, responsible) = match wedding
+        .timeline
+        .iter()
+        .find(|item| item.id == item_id)
+        .and_then(|item| {
+            Some((
+                parse_timeline_time(wedding, &item.time)?,
+                item.responsible.clone(),
+            ))
+        }) {
+        Some(delayed) => delayed,
+        None => return Vec::new(),
+    };
+    let delay = match i64::try_from(minutes)
+        .ok()
+        .and_then(chrono::Duration::try_minutes)
+    {
+        Some(delay) => delay,
+        None => return Vec::new(),
+    };
+
+    let mut shifts = Vec::new();
+    let mut timeline = wedding.timeline.clone();
+    for item in timeline.iter_mut() {
+        let start = match parse_timeline_time(wedding, &item.time) {
+            Some(start) => start,
+            None => continue,
+        };
+        let dependent = item.id != item_id
+            && start > delayed_start
+            && (move_others
+                || item
+                    .responsible
+                    .trim()
+                    .eq_ignore_ascii_case(responsible.trim()))
+            && item.started_at.is_none()
+            && item.finished_at.is_none();
+        if item.id == item_id && item.started_at.is_some() {
+            // Already running, so it runs longer instead of starting later
+            item.duration_minutes = item.duration_minutes.saturating_add(minutes);
+            item.delay_minutes = item.delay_minutes.saturating_add(minutes);
+            item.status = "delayed".to_string();
+            continue;
+        }
+        if item.id != item_id && !dependent {
+            continue;
+        }
+
+        let new_start = match start.checked_add_signed(delay) {
+            Some(new_start) => new_start,
+            None => continue,
+        };
+        let from_time = item.time.clone();
+        item.time = format_timeline_time(wedding, &from_time, new_start);
+        item.delay_minutes = item.delay_minutes.saturating_add(minutes);
+        if item.id == item_id {
+            item.status = "delayed".to_string();
+        }
+        shifts.push(TimelineShift {
+            item_id: item.id,
+            description: item.description.clone(),
+            responsible: item.responsible.clone(),
+            minutes,
+            from_time,
+            to_time: item.time.clone(),
+            caused_by: item_id,
+            shifted_at: shifted_at.to_string(),
+        });
+    }
+    wedding.timeline = timeline;
+
+    // Let booked vendors know their items moved
+    let vendors = booked_vendors(wedding);
+    for shift in shifts.iter() {
+        let vendor = vendors.iter().find(|vendor| {
+            vendor
+                .name
+                .trim()
+                .eq_ignore_ascii_case(shift.responsible.trim())
+        });
+        if let Some(vendor) = vendor {
+            wedding.vendor_notifications.push(VendorNotification {
+                id: generate_uuid(),
+                vendor_id: vendor.id,
+                item_id: shift.item_id,
+                message: format!(
+                    "\"{}\" has moved {} minutes later, from {} to {}",
+                    shift.description, shift.minutes, shift.from_time, shift.to_time
+                ),
+                created_at: shift.shifted_at.clone(),
+            });
+        }
+    }
+    wedding.timeline_shifts.extend(shifts.iter().cloned());
+    shifts
+}
+
+// What is running now, what is next and how far behind the day is
+fn build_live_status(wedding: &Wedding) -> LiveTimelineStatus {
+    let now = current_datetime();
+    let timeline = sorted_timeline(wedding);
+
+    let current: Vec<TimelineItem> = timeline
+        .iter()
+        .filter(|item| item.finished_at.is_none())
+        .filter(|item| {
+            if item.started_at.is_some() {
+                return true;
+            }
+            match (
+                parse_timeline_time(wedding, &item.time),
+                timeline_item_end(wedding, item),
+            ) {
+                (Some(start), Some(end)) => start <= now && now < end,
+                _ => false,
+            }
+        })
+        .cloned()
+        .collect();
+    let next = timeline
+        .iter()
+        .find(|item| {
+            item.started_at.is_none()
+                && item.finished_at.is_none()
+                && !current.iter().any(|running| running.id == item.id)
+        })
+        .cloned();
+
+    // Delays already applied, plus any time the next item is overdue to start
+    let minutes_behind = match &next {
+        Some(item) => {
+            let overdue = parse_timeline_time(wedding, &item.time)
+                .filter(|start| *start < now)
+                .map_or(0, |start| (now - start).num_minutes().max(0) as u64);
+            item.delay_minutes + overdue
+        }
+        None => 0,
+    };
+
+    LiveTimelineStatus {
+        now: now.format("%Y-%m-%d %H:%M").to_string(),
+        current,
+        next,
+        minutes_behind,
+        shifts: wedding.timeline_shifts.clone(),
+    }
+}
+
+// Tasks ordered so every task comes after its dependencies; None if there is a cycle
+fn task_order(tasks: &[Task]) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = Vec::new();
+    while order.len() < tasks.len() {
+        let ready = (0..tasks.len()).find(|index| {
+            !order.contains(index)
+                && tasks[*index].depends_on.iter().all(|dependency| {
+                    order.iter().any(|done| tasks[*done].id == *dependency)
+                        || !tasks.iter().any(|task| task.id == *dependency)
+                })
+        });
+        match ready {
+            Some(index) => order.push(index),
+            None => return None,
+        }
+    }
+    Some(order)
+}
+
+// Check a task's dependencies exist, don't include itself and don't form a cycle
+fn validate_task_dependencies(
+    tasks: &[Task],
+    task_id: u64,
+    depends_on: &[u64],
+) -> Result<(), Message> {
+    if depends_on.contains(&task_id) {
+        return Err(Message::InvalidInput(
+            "A task can't depend on itself".to_string(),
+        ));
+    }
+    if let Some(missing) = depends_on
+        .iter()
+        .find(|dependency| !tasks.iter().any(|task| task.id == **dependency))
+    {
+        return Err(Message::InvalidInput(format!(
+            "Task with ID {} not found",
+            missing
+        )));
+    }
+
+    let mut tasks = tasks.to_vec();
+    if let Some(task) = tasks.iter_mut().find(|task| task.id == task_id) {
+        task.depends_on = depends_on.to_vec();
+    }
+    if task_order(&tasks).is_none() {
+        return Err(Message::InvalidInput(
+            "These dependencies would create a cycle".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Earliest schedule of every task from today, the critical path and infeasible deadlines
+fn build_critical_path(wedding: &Wedding) -> CriticalPathReport {
+    let today = current_datetime().date();
+    let wedding_date = parse_date(&wedding.date);
+    let order = task_order(&wedding.tasks).unwrap_or_default();
+
+    // Earliest finish of each task, and the dependency that holds it up the longest
+    let mut finishes: HashMap<u64, chrono::NaiveDate> = HashMap::new();
+    let mut starts: HashMap<u64, chrono::NaiveDate> = HashMap::new();
+    let mut held_up_by: HashMap<u64, u64> = HashMap::new();
+    for index in order.iter() {
+        let task = &wedding.tasks[*index];
+        let blocker = task
+            .depends_on
+            .iter()
+            .filter_map(|dependency| {
+                finishes
+                    .get(dependency)
+                    .map(|finish| (*dependency, *finish))
+            })
+            .max_by_key(|(_, finish)| *finish);
+        let start = match blocker {
+            Some((dependency, finish)) if finish > today => {
+                held_up_by.insert(task.id, dependency);
+                finish
+            }
+            _ => today,
+        };
+        // Durations too large for the calendar push the finish out as far as it goes
+        let finish = if task.status == "completed" {
+            today
+        } else {
+            i64::try_from(task.duration_days)
+                .ok()
+                .and_then(chrono::Duration::try_days)
+                .and_then(|duration| start.checked_add_signed(duration))
+                .unwrap_or(chrono::NaiveDate::MAX)
+        };
+        starts.insert(task.id, start);
+        finishes.insert(task.id, finish);
+    }
+
+    // Walk back from the open task that finishes last
+    let last = order
+        .iter()
+        .map(|index| &wedding.tasks[*index])
+        .filter(|task| task.status != "completed")
+        .max_by_key(|task| finishes.get(&task.id).copied());
+    let mut critical_path = Vec::new();
+    let mut current = last.map(|task| task.id);
+    while let Some(task_id) = current {
+        critical_path.insert(0, task_id);
+        current = held_up_by.get(&task_id).copied();
+    }
+    let finish_date = last
+        .and_then(|task| finishes.get(&task.id).copied())
+        .unwrap_or(today);
+
+    let mut schedule = Vec::new();
+    let mut infeasible = Vec::new();
+    for index in order.iter() {
+        let task = &wedding.tasks[*index];
+        let start = starts.get(&task.id).copied().unwrap_or(today);
+        let finish = finishes.get(&task.id).copied().unwrap_or(today);
+        schedule.push(TaskSchedule {
+            task_id: task.id,
+            title: task.title.clone(),
+            status: task.status.clone(),
+            duration_days: task.duration_days,
+            earliest_start: start.format("%Y-%m-%d").to_string(),
+            earliest_finish: finish.format("%Y-%m-%d").to_string(),
+            deadline: task.deadline.clone(),
+            on_critical_path: critical_path.contains(&task.id),
+        });
+
+        // Every open task must also be done by the wedding
+        if task.status == "completed" {
+            continue;
+        }
+        let deadline = match (parse_date(&task.deadline), wedding_date) {
+            (Some(deadline), Some(wedding_date)) => Some(deadline.min(wedding_date)),
+            (deadline, wedding_date) => deadline.or(wedding_date),
+        };
+        if let Some(deadline) = deadline.filter(|deadline| finish > *deadline) {
+            infeasible.push(InfeasibleTask {
+                task_id: task.id,
+                title: task.title.clone(),
+                deadline: deadline.format("%Y-%m-%d").to_string(),
+                earliest_finish: finish.format("%Y-%m-%d").to_string(),
+                days_late: (finish - deadline).num_days() as u64,
+            });
+        }
+    }
+
+    CriticalPathReport {
+        wedding_date: wedding.date.clone(),
+        critical_path,
+        finish_date: finish_date.format("%Y-%m-%d").to_string(),
+        days_to_spare: wedding_date
+            .map_or(0, |wedding_date| (wedding_date - finish_date).num_days()),
+        schedule,
+        infeasible,
+    }
+}
+
+// Built-in checklist template items: (title, days before the wedding, days of work)
+const STANDARD_CHECKLIST: [(&str, u64, u64); 15] = [
+    ("Set the budget", 390, 7),
+    ("Draft the guest list", 365, 14),
+    ("Book the venue", 365, 30),
+    ("Book the photographer", 300, 14),
+    ("Book the caterer", 300, 14),
+    ("Send save-the-dates", 240, 7),
+    ("Order the wedding attire", 240, 30),
+    ("Book the music", 210, 14),
+    ("Book the florist", 180, 14),
+    ("Arrange guest transport", 120, 14),
+    ("Send invitations", 56, 7),
+    ("Finalise the menu", 42, 7),
+    ("Chase outstanding RSVPs", 28, 7),
+    ("Confirm the seating plan", 14, 3),
+    ("Confirm timings with vendors", 7, 2),
+];
+const SHORT_ENGAGEMENT_CHECKLIST: [(&str, u64, u64); 9] = [
+    ("Set the budget", 180, 3),
+    ("Book the venue", 170, 14),
+    ("Book the photographer and caterer", 150, 14),
+    ("Send invitations", 120, 7),
+    ("Order the wedding attire", 120, 30),
+    ("Arrange guest transport", 60, 7),
+    ("Chase outstanding RSVPs", 28, 7),
+    ("Confirm the seating plan", 14, 3),
+    ("Confirm timings with vendors", 7, 2),
+];
+
+// Store the built-in checklist templates, once
+fn seed_checklist_templates() {
+    let seeded = TEMPLATE_STORAGE.with(|storage| {
+        storage
+            .borrow()
+            .iter()
+            .any(|(_, template)| template.built_in)
+    });
+    if seeded {
+        return;
+    }
+
+    let builtins = [
+        (
+            "Standard wedding checklist",
+            "A year of planning, from budget to final vendor timings",
+            &STANDARD_CHECKLIST[..],
+        ),
+        (
+            "Short engagement checklist",
+            "The essentials for a wedding six months away",
+            &SHORT_ENGAGEMENT_CHECKLIST[..],
+        ),
+    ];
+    for (name, description, items) in builtins {
+        let template = ChecklistTemplate {
+            id: generate_uuid(),
+            name: name.to_string(),
+            description: description.to_string(),
+            items: items
+                .iter()
+                .map(|(title, days_before, duration_days)| ChecklistItem {
+                    title: title.to_string(),
+                    description: "".to_string(),
+                    days_before: *days_before,
+                    duration_days: *duration_days,
+                    assigned_to: "".to_string(),
+                })
+                .collect(),
+            built_in: true,
+        };
+        TEMPLATE_STORAGE.with(|storage| storage.borrow_mut().insert(template.id, template));
+    }
+}
+
+// Find a checklist template; without an ID, the first built-in one
+fn find_checklist_template(template_id: Option<u64>) -> Option<ChecklistTemplate> {
+    TEMPLATE_STORAGE.with(|storage| {
+        let storage = storage.borrow();
+        match template_id {
+            Some(template_id) => storage.get(&template_id),
+            None => storage
+                .iter()
+                .map(|(_, template)| template)
+                .find(|template| template.built_in),
+        }
+    })
+}
+
+// Turn a checklist template into tasks; tasks from an earlier run move with the wedding
+// date while they are untouched, and are otherwise left alone
+fn apply_checklist(
+    wedding: &mut Wedding,
+    template: &ChecklistTemplate,
+) -> Result<Vec<Task>, Message> {
+    let wedding_date = match parse_date(&wedding.date) {
+        Some(date) => date,
+        None => {
+            return Err(Message::InvalidDate(
+                "The wedding needs a YYYY-MM-DD date to schedule a checklist".to_string(),
+            ))
+        }
+    };
+
+    let mut changed = Vec::new();
+    for item in template.items.iter() {
+        let deadline = match i64::try_from(item.days_before)
+            .ok()
+            .and_then(chrono::Duration::try_days)
+            .and_then(|days_before| wedding_date.checked_sub_signed(days_before))
+        {
+            Some(deadline) => deadline.format("%Y-%m-%d").to_string(),
+            None => {
+                return Err(Message::InvalidDate(format!(
+                    "\"{}\" is scheduled too far before the wedding",
+                    item.title
+                )))
+            }
+        };
+        let origin = ChecklistOrigin {
+            template_id: template.id,
+            days_before: item.days_before,
+            generated_deadline: deadline.clone(),
+        };
+
+        let existing = wedding.tasks.iter_mut().find(|task| {
+            task.title == item.title
+                && task
+                    .checklist
+                    .as_ref()
+                    .is_some_and(|checklist| checklist.template_id == template.id)
+        });
+        match existing {
+            Some(task) => {
+                let untouched = (task.status == "pending" || task.status == "overdue")
+                    && task
+                        .checklist
+                        .as_ref()
+                        .is_some_and(|checklist| checklist.generated_deadline == task.deadline);
+                if untouched && task.deadline != deadline {
+                    task.deadline = deadline;
+                    task.status = "pending".to_string(); // The next sweep marks it again if needed
+                    task.checklist = Some(origin);
+                    changed.push(task.clone());
+                }
+            }
+            None => {
+                let task = Task {
+                    id: generate_uuid(),
+                    title: item.title.clone(),
+                    description: item.description.clone(),
+                    deadline,
+                    assigned_to: item.assigned_to.clone(),
+                    status: "pending".to_string(),
+                    budget: 0,
+                    duration_days: item.duration_days,
+                    depends_on: Vec::new(),
+                    checklist: Some(origin),
+                };
+                wedding.tasks.push(task.clone());
+                changed.push(task);
+            }
+        }
+    }
+    Ok(changed)
+}
+
+// Find a registry item by ID, falling back to a name lookup for older clients
+fn find_registry_item(wedding: &Wedding, item_id: Option<u64>, item_name: &str) -> Option<usize> {
+    match item_id {
+        Some(item_id) => wedding.registry.iter().position(|item| item.id == item_id),
+        None => wedding
+            .registry
+            .iter()
+            .position(|item| item.name == item_name),
+    }
+}
+
+// Whole units of a registry item paid for by group contributions
+fn registry_units_funded(item: &RegistryItem) -> u64 {
+    item.amount_funded.checked_div(item.price).unwrap_or(0)
+}
+
+// Amount still needed once both purchased units and contributions are counted
+fn registry_item_remaining(item: &RegistryItem) -> u64 {
+    let units_left = item.quantity_desired.saturating_sub(item.quantity_received);
+    item.price
+        .saturating_mul(units_left)
+        .saturating_sub(item.amount_funded)
+}
+
+// Hand a partly covered item back to guests, as "funding" once contributions have started
+fn reopen_registry_item(item: &mut RegistryItem) {
+    item.status = if item.amount_funded > 0 {
+        "funding".to_string()
+    } else {
+        "available".to_string()
+    };
+}
+
+// Check if a registry item is held by an unexpired reservation
+fn is_reservation_active(item: &RegistryItem) -> bool {
+    reservation_active_at(item, ic_cdk::api::time())
+}
+
+// Check if a registry item is held by a reservation that hasn't expired at the given time
+fn reservation_active_at(item: &RegistryItem, now: u64) -> bool {
+    item.status == "reserved" && item.reserved_until.is_some_and(|until| until > now)
+}
+
+// Hold a registry item for a guest for the reservation window. Only items nobody has bought,
+// funded or reserved can be reserved
+fn reserve_registry_item_for(
+    item: &mut RegistryItem,
+    guest: Principal,
+    now: u64,
+) -> Result<(), Message> {
+    if item.kind == RegistryItemKind::Fund {
+        return Err(Message::Error(
+            "Funds take contributions and can't be reserved".to_string(),
+        ));
+    }
+
+    if reservation_active_at(item, now) {
+        return Err(Message::Error(
+            "Registry item is already reserved".to_string(),
+        ));
+    }
+
+    if item.status != "available" && item.status != "reserved" {
+        return Err(Message::Error(format!(
+            "Registry item cannot be reserved while {}",
+            item.status
+        )));
+    }
+
+    item.status = "reserved".to_string();
+    item.reserved_by = Some(guest);
+    item.reserved_until = Some(now + REGISTRY_RESERVATION_WINDOW.as_nanos() as u64);
+    Ok(())
+}
+
+// Record the purchase of one unit by the guest holding the reservation; the item stays
+// available until every unit is bought
+fn confirm_reserved_purchase(
+    item: &mut RegistryItem,
+    guest: Principal,
+    purchased_by: &str,
+    now: u64,
+    date: &str,
+) -> Result<(), Message> {
+    if !reservation_active_at(item, now) {
+        return Err(Message::Error(
+            "Registry item has no active reservation".to_string(),
+        ));
+    }
+
+    if item.reserved_by != Some(guest) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest who reserved this item can confirm the purchase".to_string(),
+        ));
+    }
+
+    item.purchases.push(RegistryPurchase {
+        id: generate_uuid(),
+        purchased_by: purchased_by.to_string(),
+        quantity: 1,
+        date: date.to_string(),
+    });
+    item.quantity_received += 1;
+    item.purchased_by = purchased_by.to_string();
+    item.reserved_until = None;
+    if registry_item_remaining(item) == 0 {
+        item.status = "purchased".to_string();
+    } else {
+        reopen_registry_item(item);
+        item.reserved_by = None;
+    }
+    Ok(())
+}
+
+// Make a reserved item available again once its reservation has lapsed
+fn release_lapsed_reservation(item: &mut RegistryItem, now: u64) -> bool {
+    if item.status != "reserved" || item.reserved_until.is_some_and(|until| until > now) {
+        return false;
+    }
+    item.status = "available".to_string();
+    item.reserved_by = None;
+    item.reserved_until = None;
+    true
+}
+
+// Add a contribution to a registry item or fund, rejecting amounts over what is still needed
+fn apply_registry_contribution(
+    item: &mut RegistryItem,
+    amount: u64,
+    experience_id: Option<u64>,
+    now: u64,
+) -> Result<(), Message> {
+    if item.kind == RegistryItemKind::Fund {
+        // Funds stay open after reaching their target; only experiences are capped
+        if let Some(experience_id) = experience_id {
+            let experience = item
+                .experiences
+                .iter_mut()
+                .find(|experience| experience.id == experience_id);
+
+            let experience = match experience {
+                Some(experience) => experience,
+                None => {
+                    return Err(Message::Error("Fund experience not found".to_string()));
+                }
+            };
+
+            let remaining = experience.amount.saturating_sub(experience.amount_funded);
+            if amount > remaining {
+                return Err(Message::InvalidInput(format!(
+                    "Contribution exceeds the remaining amount of {} for {}",
+                    remaining, experience.name
+                )));
+            }
+            experience.amount_funded += amount;
+        }
+
+        item.amount_funded += amount;
+        item.status = if item.amount_funded >= item.price {
+            "fulfilled".to_string()
+        } else {
+            "funding".to_string()
+        };
+    } else {
+        if experience_id.is_some() {
+            return Err(Message::InvalidInput(
+                "Experiences can only be funded on a fund".to_string(),
+            ));
+        }
+
+        if item.status == "fulfilled" || item.status == "purchased" {
+            return Err(Message::Error(
+                "Registry item is no longer accepting contributions".to_string(),
+            ));
+        }
+
+        if reservation_active_at(item, now) {
+            return Err(Message::Error(
+                "Registry item is reserved by another guest".to_string(),
+            ));
+        }
+
+        // Reject contributions that would take the item over the price of its unbought units
+        let remaining = registry_item_remaining(item);
+        if amount > remaining {
+            return Err(Message::InvalidInput(format!(
+                "Contribution exceeds the remaining amount of {}",
+                remaining
+            )));
+        }
+
+        item.amount_funded += amount;
+        item.status = if registry_item_remaining(item) == 0 {
+            "fulfilled".to_string()
+        } else {
+            "funding".to_string()
+        };
+    }
+
+    Ok(())
+}
+
+// Hide purchaser and contributor names when the couple asked for it
+fn redact_registry_purchasers(mut wedding: Wedding) -> Wedding {
+    if wedding.hide_registry_purchasers && !wedding_has_passed(&wedding) {
+        for item in wedding.registry.iter_mut() {
+            item.purchased_by = "".to_string();
+            item.reserved_by = None;
+            for purchase in item.purchases.iter_mut() {
+                purchase.purchased_by = "".to_string();
+            }
+            for contribution in item.contributions.iter_mut() {
+                contribution.contributor = "".to_string();
+            }
+        }
+    }
+    wedding
+}
+
+// Link a gift giver to a guest by email or name
+fn find_guest_email_for(wedding: &Wedding, giver: &str) -> Option<String> {
+    let giver = giver.trim().to_lowercase();
+    if giver.is_empty() {
+        return None;
+    }
+    wedding
+        .guest_list
+        .iter()
+        .find(|guest| {
+            guest.guest_email.to_lowercase() == giver || guest.name.to_lowercase() == giver
+        })
+        .map(|guest| guest.guest_email.clone())
+        .filter(|email| !email.is_empty())
+}
+
+// Release a registry reservation once its window has lapsed
+fn release_expired_reservation(wedding_id: u64, item_id: u64) {
+    WEDDING_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        if let Some(mut wedding) = storage.get(&wedding_id) {
+            let now = ic_cdk::api::time();
+            let mut released = false;
+            for item in wedding.registry.iter_mut() {
+                if item.id == item_id && release_lapsed_reservation(item, now) {
+                    released = true;
+                }
+            }
+            if released {
+                storage.insert(wedding_id, wedding);
+            }
+        }
+    });
+}
+
+// End of the RSVP deadline day (UTC) in nanoseconds
+fn rsvp_deadline_end(wedding: &Wedding) -> Option<u64> {
+    let deadline = parse_date(wedding.rsvp_deadline.as_deref()?)?;
+    let nanos = deadline
+        .succ_opt()?
+        .and_hms_opt(0, 0, 0)?
+        .and_utc()
+        .timestamp_nanos_opt()?;
+    u64::try_from(nanos).ok()
+}
+
+// Check if a guest still owes the couple an answer: invited, or pending without a recorded response
+fn awaiting_rsvp(guest: &Guest) -> bool {
+    guest.rsvp_status == "invited"
+        || (guest.rsvp_status == "pending" && guest.rsvp_history.is_empty())
+}
+
+// Schedule the reminder and deadline timers for a wedding, replacing any earlier ones
+fn schedule_rsvp_timers(wedding: &Wedding) {
+    let wedding_id = wedding.id;
+    RSVP_TIMERS.with(|timers| {
+        if let Some(timer_ids) = timers.borrow_mut().remove(&wedding_id) {
+            timer_ids.into_iter().for_each(ic_cdk_timers::clear_timer);
+        }
+    });
+
+    let deadline_end = match rsvp_deadline_end(wedding) {
+        Some(deadline_end) if !wedding.rsvps_closed => deadline_end,
+        _ => return,
+    };
+    let now = ic_cdk::api::time();
+    let mut timer_ids = Vec::new();
+
+    // Reminders whose time has already passed are skipped rather than sent late
+    for days_before in wedding.rsvp_reminder_days.iter().copied() {
+        let remind_at = deadline_end.saturating_sub(days_before.saturating_mul(NANOS_PER_DAY));
+        if remind_at <= now {
+            continue;
+        }
+        let delay = Duration::from_nanos(remind_at - now);
+        timer_ids.push(ic_cdk_timers::set_timer(delay, move || {
+            queue_rsvp_reminders(wedding_id, days_before)
+        }));
+    }
+
+    let delay = Duration::from_nanos(deadline_end.saturating_sub(now));
+    timer_ids.push(ic_cdk_timers::set_timer(delay, move || {
+        close_rsvps(wedding_id)
+    }));
+
+    RSVP_TIMERS.with(|timers| {
+        timers.borrow_mut().insert(wedding_id, timer_ids);
+    });
+}
+
+// Queue a reminder for every guest who hasn't responded yet
+fn queue_rsvp_reminders(wedding_id: u64, days_before: u64) {
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+    let mut wedding = match wedding {
+        Some(wedding) if !wedding.rsvps_closed => wedding,
+        _ => return,
+    };
+    let deadline = match wedding.rsvp_deadline.clone() {
+        Some(deadline) => deadline,
+        None => return,
+    };
+
+    let mut queued = Vec::new();
+    for guest in wedding.guest_list.iter() {
+        let already_reminded = wedding.rsvp_reminders.iter().any(|reminder| {
+            reminder.guest_id == guest.id
+                && reminder.days_before == days_before
+                && reminder.rsvp_deadline == deadline
+        });
+        if !awaiting_rsvp(guest) || already_reminded {
+            continue;
+        }
+
+        queued.push(RsvpReminder {
+            id: generate_uuid(),
+            guest_id: guest.id,
+            guest_name: guest.name.clone(),
+            guest_email: guest.guest_email.clone(),
+            rsvp_deadline: deadline.clone(),
+            days_before,
+            message: format!(
+                "Please RSVP to the wedding of {} by {}",
+                wedding.couple_names.join(" & "),
+                deadline
+            ),
+            queued_at: current_timestamp(),
+        });
+    }
+
+    if !queued.is_empty() {
+        wedding.rsvp_reminders.extend(queued);
+        WEDDING_STORAGE.with(|storage| {
+            storage.borrow_mut().insert(wedding_id, wedding);
+        });
+    }
+}
+
+// Apply the deadline action to guests who never responded
+fn close_rsvps(wedding_id: u64) {
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+    let mut wedding = match wedding {
+        Some(wedding) if !wedding.rsvps_closed && rsvp_deadline_passed(&wedding) => wedding,
+        _ => return,
+    };
+
+    let status = match wedding.rsvp_deadline_action {
+        RsvpDeadlineAction::Flag => "no-response",
+        RsvpDeadlineAction::Decline => "declined",
+    };
+    for guest in wedding.guest_list.iter_mut() {
+        if awaiting_rsvp(guest) {
+            guest.rsvp_status = status.to_string();
+            record_rsvp_response(guest);
+        }
+    }
+    wedding.rsvps_closed = true;
+
+    WEDDING_STORAGE.with(|storage| {
+        storage.borrow_mut().insert(wedding_id, wedding);
+    });
+    RSVP_TIMERS.with(|timers| timers.borrow_mut().remove(&wedding_id));
+}
+
+// Check if a task is overdue: marked so, or pending past its deadline
+fn task_is_overdue(task: &Task, today: chrono::NaiveDate) -> bool {
+    task.status == "overdue"
+        || (task.status == "pending"
+            && parse_date(&task.deadline).is_some_and(|deadline| deadline < today))
+}
+
+// Check if a timeline item is overdue: marked so, or should have finished without starting.
+// Items whose end can't be worked out are left alone, so one bad item can't trap the sweep
+fn timeline_item_is_overdue(
+    wedding: &Wedding,
+    item: &TimelineItem,
+    now: chrono::NaiveDateTime,
+) -> bool {
+    item.status == "overdue"
+        || (item.status == "pending"
+            && item.started_at.is_none()
+            && timeline_item_end(wedding, item).is_some_and(|end| end < now))
+}
+
+// Mark a wedding's past-deadline pending tasks and timeline items as overdue
+fn mark_overdue(wedding: &mut Wedding) -> bool {
+    let now = current_datetime();
+    let mut changed = false;
+    for task in wedding.tasks.iter_mut() {
+        if task.status == "pending" && task_is_overdue(task, now.date()) {
+            task.status = "overdue".to_string();
+            changed = true;
+        }
+    }
+
+    let overdue_items: Vec<u64> = wedding
+        .timeline
+        .iter()
+        .filter(|item| item.status == "pending" && timeline_item_is_overdue(wedding, item, now))
+        .map(|item| item.id)
+        .collect();
+    for item in wedding.timeline.iter_mut() {
+        if overdue_items.contains(&item.id) {
+            item.status = "overdue".to_string();
+            changed = true;
+        }
+    }
+    changed
+}
+
+// Sweep the next batch of weddings for overdue work, wrapping around at the end
+fn sweep_overdue() {
+    let start = OVERDUE_SWEEP_CURSOR.with(|cursor| *cursor.borrow());
+    let batch: Vec<(u64, Wedding)> = WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow()
+            .range(start..)
+            .take(OVERDUE_SWEEP_BATCH_SIZE + 1)
+            .collect()
+    });
+
+    // The extra wedding, if any, is where the next sweep picks up
+    let next = if batch.len() > OVERDUE_SWEEP_BATCH_SIZE {
+        batch[OVERDUE_SWEEP_BATCH_SIZE].0
+    } else {
+        0
+    };
+    OVERDUE_SWEEP_CURSOR.with(|cursor| *cursor.borrow_mut() = next);
+
+    for (wedding_id, mut wedding) in batch.into_iter().take(OVERDUE_SWEEP_BATCH_SIZE) {
+        if mark_overdue(&mut wedding) {
+            WEDDING_STORAGE.with(|storage| {
+                storage.borrow_mut().insert(wedding_id, wedding);
+            });
+        }
+    }
+}
+
+// Start the periodic overdue sweep
+fn start_overdue_sweep() {
+    ic_cdk_timers::set_timer_interval(OVERDUE_SWEEP_INTERVAL, sweep_overdue);
+}
+
+/**
+ * Canister Definition - Implementation of core functions
+ */
+
+/**
+ * Vendor Management
+ */
+
+#[ic_cdk::update]
+fn register_vendor(payload: RegisterVendorPayload) -> Result<(Vendor, Message), Message> {
+    // Validate the user input to ensure all required fields are provided
+    if payload.name.is_empty() && payload.description.is_empty() && payload.service_cost == 0 {
+        return Err(Message::InvalidInput(
+            "Ensure all required fileds are provided.".to_string(),
+        ));
+    }
+
+    // Generate a unique ID for the vendor
+    let vendor_id = generate_uuid();
+
+    let vendor = Vendor {
+        id: vendor_id,
+        owner: caller(),
+        name: payload.name,
+        category: payload.category,
+        description: payload.description,
+        service_cost: payload.service_cost,
+        availability: payload.availability,
+        rating: 0,
+        reviews: Vec::new(),
+        bookings: Vec::new(),
+        verified: false,
+        portfolio: payload.portfolio,
+    };
+
+    // Store vendor in VENDOR_STORAGE
+    VENDOR_STORAGE.with(|vendors| {
+        vendors.borrow_mut().insert(vendor_id, vendor.clone());
+    });
+
+    Ok((
+        vendor,
+        Message::Success("Vendor registered successfully".to_string()),
+    ))
+}
+
+// Book Vendor for Wedding
+#[ic_cdk::update]
+fn book_vendor(
+    payload: VendorBookingPayload,
+) -> Result<(String, Wedding, Vendor, VendorBooking), Message> {
+    // Fetch the wedding and vendor from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+    let vendor = VENDOR_STORAGE.with(|storage| storage.borrow().get(&payload.vendor_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    // Validate vendor existence
+    let vendor = match vendor {
+        Some(vendor) => vendor.clone(),
+        None => {
+            return Err(Message::VendorNotFound(format!(
+                "Vendor with ID {} not found",
+                payload.vendor_id
+            )))
+        }
+    };
+
+    // Check vendor availability
+    if !vendor.availability.iter().any(|date| date == &wedding.date) {
+        return Err(Message::DateUnavailable(format!(
+            "Vendor not available on wedding date {}",
+            wedding.date
+        )));
+    }
+
+    // Create the vendor booking
+    let vendor_booking = VendorBooking {
+        vendor_id: payload.vendor_id,
+        wedding_id: payload.wedding_id,
+        wedding_offer: payload.wedding_offer,
+        additional_details: payload.additional_details.clone(),
+        status: "pending".to_string(),
+        date: wedding.date.clone(),
+    };
+
+    // Update the wedding with the new vendor booking
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.vendors.push(vendor_booking.clone());
+
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Update the vendor with the new booking
+    let mut updated_vendor = vendor.clone();
+    updated_vendor.bookings.push(wedding.id.to_string());
+
+    VENDOR_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.vendor_id, updated_vendor.clone());
+    });
+
+    // Return success
+    Ok((
+        "Vendor booked successfully".to_string(),
+        wedding_view(updated_wedding),
+        vendor,
+        vendor_booking,
+    ))
+}
+
+// Verify Vendor Booking done by the Vendor
+#[ic_cdk::update]
+fn verify_vendor_booking(
+    vendor_id: u64,
+    wedding_id: u64,
+) -> Result<(VendorBooking, Message), Message> {
+    // Fetch the vendor from storage
+    let vendor = VENDOR_STORAGE.with(|storage| storage.borrow().get(&vendor_id));
+
+    // Validate vendor existence
+    let vendor = match vendor {
+        Some(vendor) => vendor.clone(),
+        None => {
+            return Err(Message::VendorNotFound(format!(
+                "Vendor with ID {} not found",
+                vendor_id
+            )))
+        }
+    };
+
+    if vendor.owner != caller() {
+        return Err(Message::UnauthorizedAction(
+            "You are not authorized to perform this action".to_string(),
+        ));
+    }
+
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    // Fetch the vendor booking from the wedding
+    let booking = wedding
+        .vendors
+        .iter()
+        .find(|booking| booking.vendor_id == vendor_id);
+
+    let booking = match booking {
+        Some(booking) => booking.clone(),
+        None => {
+            return Err(Message::Error("Vendor booking not found".to_string()));
+        }
+    };
+
+    // Update the vendor booking status
+    let mut updated_booking = booking.clone();
+    updated_booking.status = "confirmed".to_string();
+
+    // Save the updated vendor booking details
+    WEDDING_STORAGE.with(|storage| {
+        let mut updated_wedding = wedding.clone();
+        updated_wedding.vendors = updated_wedding
+            .vendors
+            .iter()
+            .map(|booking| {
+                if booking.vendor_id == vendor_id {
+                    updated_booking.clone()
+                } else {
+                    booking.clone()
+                }
+            })
+            .collect();
+
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        updated_booking,
+        Message::Success("Vendor booking verified successfully".to_string()),
+    ))
+}
+
+// Verify Vendor
+#[ic_cdk::update]
+fn verify_vendor(vendor_id: u64) -> Result<(Vendor, Message), Message> {
+    // Fetch the vendor from storage
+    let vendor = VENDOR_STORAGE.with(|storage| storage.borrow().get(&vendor_id));
+
+    // Validate vendor existence
+    let vendor = match vendor {
+        Some(vendor) => vendor.clone(),
+        None => {
+            return Err(Message::VendorNotFound(format!(
+                "Vendor with ID {} not found",
+                vendor_id
+            )))
+        }
+    };
+
+    // Update the vendor's verified status
+    let mut updated_vendor = vendor.clone();
+    updated_vendor.verified = true;
+
+    // Save the updated vendor details
+    VENDOR_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(vendor_id, updated_vendor.clone());
+    });
+
+    // Return success
+    Ok((
+        updated_vendor,
+        Message::Success("Vendor verified successfully".to_string()),
+    ))
+}
+
+/**
+ * Vendor Queries
+ */
+
+// Get Vendor Details by ID
+#[ic_cdk::query]
+fn get_vendor_details(vendor_id: u64) -> Result<Vendor, Message> {
+    VENDOR_STORAGE.with(|vendors| match vendors.borrow().get(&vendor_id) {
+        Some(vendor) => Ok(vendor.clone()),
+        None => Err(Message::VendorNotFound(format!(
+            "Vendor with id={} not found",
+            vendor_id
+        ))),
+    })
+}
+
+// Get Vendors by Category
+#[ic_cdk::query]
+fn search_vendors_by_category(category: Category) -> Result<Vec<Vendor>, Message> {
+    let matching_vendors: Vec<Vendor> = VENDOR_STORAGE.with(|vendors| {
+        vendors
+            .borrow()
+            .iter()
+            .filter_map(|(_, vendor)| {
+                if vendor.category == category {
+                    Some(vendor.clone()) // Include matching vendor
+                } else {
+                    None // Skip non-matching vendor
+                }
+            })
+            .collect() // Collect all matching vendors into a Vec
+    });
+
+    if matching_vendors.is_empty() {
+        Err(Message::VendorNotFound(format!(
+            "No vendors found in the '{}' category",
+            format!("{:?}", category) // Convert the enum to a readable string
+        )))
+    } else {
+        Ok(matching_vendors)
+    }
+}
+
+// Get All Vendors
+#[ic_cdk::query]
+fn get_all_vendors() -> Result<Vec<Vendor>, Message> {
+    VENDOR_STORAGE.with(|vendors| {
+        let all_vendors: Vec<Vendor> = vendors
+            .borrow()
+            .iter()
+            .map(|(_, vendor)| vendor.clone()) // Clone each vendor to return owned data
+            .collect();
+
+        if all_vendors.is_empty() {
+            Err(Message::VendorNotFound("No vendors found.".to_string()))
+        } else {
+            Ok(all_vendors)
+        }
+    })
+}
+
+/**
+ * Wedding Management
+ */
+#[ic_cdk::update]
+fn create_wedding(payload: CreateWeddingPayload) -> Result<(Wedding, Message), Message> {
+    // Validate the user input to ensure all required fields are provided
+    if payload.couple_names.is_empty()
+        && payload.date.is_empty()
+        && payload.budget == 0
+        && payload.location.is_empty()
+        && payload.guest_count == 0
+    {
+        return Err(Message::InvalidInput(
+            "Ensure all required fileds are provided.".to_string(),
+        ));
+    }
+
+    // The creator becomes the owner, and every unauthenticated client shares one principal
+    if caller() == Principal::anonymous() {
+        return Err(Message::UnauthorizedAction(
+            "Sign in before creating a wedding".to_string(),
+        ));
+    }
+
+    // Start the wedding with a planning checklist
+    let template = find_checklist_template(payload.checklist_template_id);
+    if payload.checklist_template_id.is_some() && template.is_none() {
+        return Err(Message::Error("Checklist template not found".to_string()));
+    }
+
+    // Generate a unique ID for the wedding
+    let wedding_id = generate_uuid();
+
+    let mut wedding = Wedding {
+        id: wedding_id,
+        owner: caller(),
+        couple_names: payload.couple_names,
+        date: payload.date,
+        budget: payload.budget,
+        location: payload.location,
+        guest_count: payload.guest_count,
+        vendors: Vec::new(),
+        timeline: Vec::new(),
+        timeline_shifts: Vec::new(),
+        vendor_notifications: Vec::new(),
+        tasks: Vec::new(),
+        guest_list: Vec::new(),
+        households: Vec::new(),
+        events: Vec::new(),
+        invitations: Vec::new(),
+        code_salt: "".to_string(),
+        waitlist: Vec::new(),
+        registry: Vec::new(),
+        status: "planning".to_string(),
+        rsvp_deadline: None,
+        rsvp_reminder_days: Vec::new(),
+        rsvp_deadline_action: RsvpDeadlineAction::default(),
+        rsvps_closed: false,
+        rsvp_reminders: Vec::new(),
+        meal_options: Vec::new(),
+        catering_lock_date: None,
+        check_in_staff: Vec::new(),
+        planners: Vec::new(),
+        room_blocks: Vec::new(),
+        transport_runs: Vec::new(),
+        hide_registry_purchasers: false,
+        gifts: Vec::new(),
+    };
+
+    // A chosen template needs a valid date; the default checklist is skipped without one
+    if let Some(template) = template {
+        if let Err(error) = apply_checklist(&mut wedding, &template) {
+            if payload.checklist_template_id.is_some() {
+                return Err(error);
+            }
+        }
+    }
+
+    WEDDING_STORAGE.with(|weddings| {
+        weddings.borrow_mut().insert(wedding_id, wedding.clone());
+    });
+
+    Ok((
+        wedding,
+        Message::Success("Wedding created successfully".to_string()),
+    ))
+}
+
+// Set or clear the RSVP deadline
+#[ic_cdk::update]
+fn set_rsvp_deadline(
+    wedding_id: u64,
+    rsvp_deadline: Option<String>,
+) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can set the RSVP deadline".to_string(),
+        ));
+    }
+
+    if let Some(deadline) = &rsvp_deadline {
+        if parse_date(deadline).is_none() {
+            return Err(Message::InvalidDate(
+                "RSVP deadline must be in YYYY-MM-DD format".to_string(),
+            ));
+        }
+    }
+
+    // A new deadline reopens RSVPs until it passes
+    let mut updated_wedding = wedding.clone();
+    if updated_wedding.rsvp_deadline != rsvp_deadline {
+        updated_wedding.rsvps_closed = false;
+    }
+    updated_wedding.rsvp_deadline = rsvp_deadline;
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    schedule_rsvp_timers(&updated_wedding);
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("RSVP deadline updated successfully".to_string()),
+    ))
+}
+
+// Choose when guests are reminded before the RSVP deadline, and what happens to those who never respond
+#[ic_cdk::update]
+fn set_rsvp_reminders(payload: SetRsvpRemindersPayload) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can set RSVP reminders".to_string(),
+        ));
+    }
+
+    let mut reminder_days = payload.reminder_days.clone();
+    reminder_days.sort_unstable_by(|a, b| b.cmp(a));
+    reminder_days.dedup();
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.rsvp_reminder_days = reminder_days;
+    updated_wedding.rsvp_deadline_action = payload.deadline_action.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    schedule_rsvp_timers(&updated_wedding);
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("RSVP reminders updated successfully".to_string()),
+    ))
+}
+
+// Mark a wedding completed once its date has passed
+#[ic_cdk::update]
+fn complete_wedding(wedding_id: u64) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can mark the wedding completed".to_string(),
+        ));
+    }
+
+    if !wedding_has_passed(&wedding) {
+        return Err(Message::InvalidDate(
+            "A wedding can only be completed after its date".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.status = "completed".to_string();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("Wedding marked completed successfully".to_string()),
+    ))
+}
+
+// Change the wedding date; re-applying a checklist template then moves untouched tasks
+#[ic_cdk::update]
+fn set_wedding_date(wedding_id: u64, date: String) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can change the wedding date".to_string(),
+        ));
+    }
+
+    if parse_date(&date).is_none() {
+        return Err(Message::InvalidDate(
+            "Wedding date must be in YYYY-MM-DD format".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.date = date.trim().to_string();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("Wedding date updated successfully".to_string()),
+    ))
+}
+
+/**
+ * Wedding Queries
+ */
+
+#[ic_cdk::query]
+fn get_wedding_details(wedding_id: u64) -> Result<Wedding, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow()
+            .get(&wedding_id)
+            .map(wedding_view)
+            .ok_or(Message::WeddingNotFound("Wedding not found".to_string()))
+    })
+}
+
+// Get All Weddings
+#[ic_cdk::query]
+fn get_all_weddings() -> Result<Vec<Wedding>, Message> {
+    WEDDING_STORAGE.with(|weddings| {
+        let all_weddings: Vec<Wedding> = weddings
+            .borrow()
+            .iter()
+            .map(|(_, wedding)| wedding_view(wedding.clone())) // Clone each wedding to return owned data
+            .collect();
+
+        if all_weddings.is_empty() {
+            Err(Message::WeddingNotFound("No weddings found.".to_string()))
+        } else {
+            Ok(all_weddings)
+        }
+    })
+}
+
+// Get Wedding Timeline
+#[ic_cdk::query]
+fn get_wedding_timeline(wedding_id: u64) -> Result<Vec<TimelineItem>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if wedding.timeline.is_empty() {
+                    Err(Message::NoTimeLineItemsFound(
+                        "No timeline items found for this wedding".to_string(),
+                    ))
+                } else {
+                    Ok(sorted_timeline(&wedding))
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+/*
+ * Guest Management
+ * RSVP Submission
+ * Guest RSVP Approval
+ * Table Assignment
+ * Guest Queries
+ * Guest RSVP Queries
+ * Guest RSVP List
+ * Guest RSVP Status
+ * Guest RSVP Count
+ */
+
+// Guest RSVP Submission
+#[ic_cdk::update]
+fn guest_rsvp(payload: GuestRsvpPayload) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    // Validate the user input
+    let guest_email = validate_email(&payload.guest_email)?;
+    if payload.name.is_empty() || guest_email.is_empty() {
+        return Err(Message::InvalidInput(
+            "Guest name and email are required".to_string(),
+        ));
+    }
+
+    let plus_one_name = payload
+        .plus_one_name
+        .clone()
+        .filter(|name| !name.trim().is_empty());
+    if payload.plus_one && plus_one_name.is_none() {
+        return Err(Message::InvalidInput(
+            "Please provide the name of your plus one".to_string(),
+        ));
+    }
+
+    check_rsvp_open(&wedding)?;
+
+    // Redeem the invitation code; only invited guests can RSVP
+    let mut updated_wedding = wedding.clone();
+    let invitation = redeem_invitation(&mut updated_wedding, &payload.invitation_code, caller())?;
+
+    let guest_index = match invitation
+        .guest_id
+        .and_then(|guest_id| find_guest(&updated_wedding, Some(guest_id), ""))
+    {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error(
+                "This invitation is for a household; please use the household RSVP".to_string(),
+            ));
+        }
+    };
+
+    if updated_wedding.guest_list[guest_index].rsvp_status != "invited" {
+        return Err(Message::Error("Guest RSVP already submitted".to_string()));
+    }
+
+    // Check the email isn't used by another guest
+    let email_taken = updated_wedding
+        .guest_list
+        .iter()
+        .enumerate()
+        .any(|(index, guest)| index != guest_index && same_email(&guest.guest_email, &guest_email));
+
+    if email_taken {
+        return Err(Message::Error(
+            "Another guest has already used this email".to_string(),
+        ));
+    }
+
+    // Record the guest's RSVP
+    let guest = &mut updated_wedding.guest_list[guest_index];
+    guest.name = payload.name.clone();
+    guest.guest_email = guest_email;
+    guest.rsvp_status = "pending".to_string();
+    guest.meal_choice = payload.meal_choice.clone();
+    guest.dietary_restrictions = payload.dietary_restrictions.clone();
+    guest.plus_one = payload.plus_one;
+    guest.plus_one_name = if payload.plus_one {
+        plus_one_name
+    } else {
+        None
+    };
+    record_rsvp_response(guest);
+    let guest = guest.clone();
+
+    // Claim a room in a block, if the guest asked for one
+    if let Some(request) = &payload.room {
+        claim_room_for(&mut updated_wedding, guest.id, request)?;
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Guest RSVP submitted successfully".to_string(),
+        guest_view(&updated_wedding, &guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Guest RSVP Approval and Table Assignment
+#[ic_cdk::update]
+fn approve_rsvp(payload: ApproveRsvpPayload) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planners can approve RSVPs".to_string(),
+        ));
+    }
+
+    // After the catering lock, changes to the confirmed headcount are the couple's call
+    if catering_locked(&wedding) && !is_wedding_owner(&wedding) {
+        return Err(Message::InvalidDate(
+            "The final headcount has been locked; only the couple can approve RSVPs now"
+                .to_string(),
+        ));
+    }
+
+    // Check if the guest already exists
+    let guest_index = match find_guest(&wedding, payload.guest_id, &payload.guest_email) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+    let guest = wedding.guest_list[guest_index].clone();
+
+    // Only guests who have responded can be approved; confirmed guests may be moved to another table
+    if !["pending", "waitlisted", "confirmed"].contains(&guest.rsvp_status.as_str()) {
+        return Err(Message::Error(format!(
+            "Only pending or waitlisted RSVPs can be approved, this guest is {}",
+            guest.rsvp_status
+        )));
+    }
+
+    // Confirm if there is still available seating capacity; `guest_count` holds the
+    // remaining seats, and re-approving a confirmed guest only changes their table
+    let seats_needed = if guest.rsvp_status == "confirmed" {
+        0
+    } else {
+        guest_headcount(&guest)
+    };
+
+    // Guests who don't fit go on the waitlist and are promoted as seats free up
+    if seats_needed > wedding.guest_count {
+        let mut updated_wedding = wedding.clone();
+        match updated_wedding
+            .waitlist
+            .iter_mut()
+            .find(|entry| entry.guest_id == guest.id)
+        {
+            Some(entry) => entry.table_assignment = payload.table_assignment.clone(),
+            None => updated_wedding.waitlist.push(WaitlistEntry {
+                guest_id: guest.id,
+                priority: 0,
+                table_assignment: payload.table_assignment.clone(),
+                added_at: current_timestamp(),
+            }),
+        }
+        updated_wedding.guest_list[guest_index].rsvp_status = "waitlisted".to_string();
+        let updated_guest = updated_wedding.guest_list[guest_index].clone();
+
+        let position = waitlist_position(&updated_wedding, guest.id)
+            .map(|position| position.position)
+            .unwrap_or_default();
+
+        // Save the updated wedding details
+        WEDDING_STORAGE.with(|storage| {
+            storage
+                .borrow_mut()
+                .insert(payload.wedding_id, updated_wedding.clone());
+        });
+
+        return Ok((
+            format!(
+                "No seats available; guest added to the waitlist at position {}",
+                position
+            ),
+            guest_view(&updated_wedding, &updated_guest),
+            wedding_view(updated_wedding),
+        ));
+    }
+
+    // Update the guest's RSVP status and table assignment
+    let mut updated_guest = guest.clone();
+    updated_guest.rsvp_status = "confirmed".to_string();
+    updated_guest.table_assignment = payload.table_assignment.clone();
+
+    // Update the wedding's guest list
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.guest_list[guest_index] = updated_guest.clone();
+    updated_wedding
+        .waitlist
+        .retain(|entry| entry.guest_id != guest.id);
+
+    // Update the available seats
+    updated_wedding.guest_count -= seats_needed;
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "RSVP approved and table assigned successfully".to_string(),
+        guest_view(&updated_wedding, &updated_guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Update RSVP details; a guest who declined and changes their mind goes back to pending
+#[ic_cdk::update]
+fn update_rsvp(payload: UpdateRsvpPayload) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    // Fetch the guest from the wedding
+    let guest_index = match find_guest(&wedding, payload.guest_id, &payload.guest_email) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+    let guest = wedding.guest_list[guest_index].clone();
+
+    if !can_act_for_guest(&wedding, &guest) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest or the couple can change this RSVP".to_string(),
+        ));
+    }
+    check_rsvp_open(&wedding)?;
+
+    if guest.rsvp_status == "invited" {
+        return Err(Message::Error(
+            "Please submit your RSVP with your invitation code first".to_string(),
+        ));
+    }
+
+    let plus_one_name = payload
+        .plus_one_name
+        .clone()
+        .filter(|name| !name.trim().is_empty());
+    if payload.plus_one && plus_one_name.is_none() {
+        return Err(Message::InvalidInput(
+            "Please provide the name of your plus one".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let mut updated_guest = guest.clone();
+    updated_guest.meal_choice = payload.meal_choice.clone();
+    updated_guest.dietary_restrictions = payload.dietary_restrictions.clone();
+    updated_guest.plus_one = payload.plus_one;
+    updated_guest.plus_one_name = if payload.plus_one {
+        plus_one_name
+    } else {
+        None
+    };
+    if !payload.plus_one {
+        updated_guest.plus_one_meal_option_id = None;
+    }
+
+    // Confirmed guests hold seats, so adding or dropping a plus one changes the count
+    if guest.rsvp_status == "confirmed" {
+        let seats_before = guest_headcount(&guest);
+        let seats_after = guest_headcount(&updated_guest);
+        if seats_after > seats_before {
+            let extra_seats = seats_after - seats_before;
+            if extra_seats > updated_wedding.guest_count {
+                return Err(Message::BudgetExceeded(
+                    "There are no seats left for a plus one".to_string(),
+                ));
+            }
+            updated_wedding.guest_count -= extra_seats;
+        }
+    }
+
+    // A declined guest who updates their RSVP is attending again, pending approval
+    if guest.rsvp_status == "declined" {
+        updated_guest.rsvp_status = "pending".to_string();
+    }
+    record_rsvp_response(&mut updated_guest);
+    updated_wedding.guest_list[guest_index] = updated_guest.clone();
+
+    // Claim the requested room, or check the current one still fits a new plus one
+    match &payload.room {
+        Some(request) => {
+            claim_room_for(&mut updated_wedding, updated_guest.id, request)?;
+        }
+        None => check_room_capacity(&updated_wedding, updated_guest.id)?,
+    }
+    check_transport_capacity(&updated_wedding, updated_guest.id)?;
+
+    // Dropping a plus one frees a seat for the waitlist
+    if guest.rsvp_status == "confirmed" {
+        let seats_freed = guest_headcount(&guest).saturating_sub(guest_headcount(&updated_guest));
+        release_seats(&mut updated_wedding, seats_freed);
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "RSVP updated successfully".to_string(),
+        guest_view(&updated_wedding, &updated_guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Decline an RSVP, giving back any seats the guest held
+#[ic_cdk::update]
+fn decline_rsvp(payload: RsvpChangePayload) -> Result<(String, Guest, Wedding), Message> {
+    change_rsvp(payload, "declined")
+}
+
+// Withdraw an RSVP so the guest can respond again with their invitation code
+#[ic_cdk::update]
+fn withdraw_rsvp(payload: RsvpChangePayload) -> Result<(String, Guest, Wedding), Message> {
+    change_rsvp(payload, "invited")
+}
+
+// Move a guest to "declined" or back to "invited", releasing their seats
+fn change_rsvp(
+    payload: RsvpChangePayload,
+    status: &str,
+) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    // Fetch the guest from the wedding
+    let guest_index = match find_guest(&wedding, payload.guest_id, &payload.guest_email) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+    let guest = wedding.guest_list[guest_index].clone();
+
+    if !can_act_for_guest(&wedding, &guest) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest or the couple can change this RSVP".to_string(),
+        ));
+    }
+    check_rsvp_open(&wedding)?;
+
+    if guest.rsvp_status == status {
+        return Err(Message::Error(format!("RSVP is already {}", status)));
+    }
+
+    let mut updated_guest = guest.clone();
+    updated_guest.rsvp_status = status.to_string();
+    updated_guest.table_assignment = TableAssignment::Unassigned;
+    for rsvp in updated_guest.event_rsvps.iter_mut() {
+        rsvp.status = status.to_string();
+        rsvp.table_assignment = TableAssignment::Unassigned;
+    }
+    record_rsvp_response(&mut updated_guest);
+
+    // Free the guest's seats, or their waitlist spot, for the next guests in line
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.guest_list[guest_index] = updated_guest.clone();
+    let seats_freed = if guest.rsvp_status == "confirmed" {
+        guest_headcount(&guest)
+    } else {
+        0
+    };
+    release_seats(&mut updated_wedding, seats_freed);
+    release_unused_rooms(&mut updated_wedding);
+    release_unused_transport(&mut updated_wedding);
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    let message = if status == "declined" {
+        "RSVP declined successfully"
+    } else {
+        "RSVP withdrawn successfully"
+    };
+
+    // Return success
+    Ok((
+        message.to_string(),
+        guest_view(&updated_wedding, &updated_guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Remove a guest from the wedding, freeing their seats for the waitlist
+#[ic_cdk::update]
+fn remove_guest(wedding_id: u64, guest_id: u64) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can remove guests".to_string(),
+        ));
+    }
+
+    let guest_index = match find_guest(&wedding, Some(guest_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    let mut updated_wedding = wedding.clone();
+    let guest = updated_wedding.guest_list.remove(guest_index);
+
+    // The guest's invitation code stops working
+    for invitation in updated_wedding.invitations.iter_mut() {
+        if invitation.guest_id == Some(guest.id) {
+            invitation.revoked = true;
+        }
+    }
+
+    let seats_freed = if guest.rsvp_status == "confirmed" {
+        guest_headcount(&guest)
+    } else {
+        0
+    };
+    release_seats(&mut updated_wedding, seats_freed);
+    release_unused_rooms(&mut updated_wedding);
+    release_unused_transport(&mut updated_wedding);
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        "Guest removed successfully".to_string(),
+        guest_view(&updated_wedding, &guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Update a guest's tags, side, relationship, age group and address (couple only)
+#[ic_cdk::update]
+fn update_guest_profile(
+    payload: UpdateGuestProfilePayload,
+) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can update guest profiles".to_string(),
+        ));
+    }
+
+    let guest_index = match find_guest(&wedding, Some(payload.guest_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    // Drop empty and repeated tags
+    let mut tags: Vec<String> = Vec::new();
+    for tag in payload.tags.iter().map(|tag| tag.trim()) {
+        if !tag.is_empty()
+            && !tags
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(tag))
+        {
+            tags.push(tag.to_string());
+        }
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let guest = &mut updated_wedding.guest_list[guest_index];
+    guest.tags = tags;
+    guest.side = payload.side.clone();
+    guest.relationship = payload.relationship.clone();
+    guest.age_group = payload.age_group.clone();
+    guest.address = payload.address.clone();
+    let guest = guest.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        "Guest profile updated successfully".to_string(),
+        guest_view(&updated_wedding, &guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Merge a duplicate guest into another, keeping the more complete data (couple only)
+#[ic_cdk::update]
+fn merge_guests(
+    wedding_id: u64,
+    keep_guest_id: u64,
+    merge_guest_id: u64,
+) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can merge guests".to_string(),
+        ));
+    }
+
+    if keep_guest_id == merge_guest_id {
+        return Err(Message::InvalidInput(
+            "Choose two different guests to merge".to_string(),
+        ));
+    }
+
+    let (keep_index, merge_index) = match (
+        find_guest(&wedding, Some(keep_guest_id), ""),
+        find_guest(&wedding, Some(merge_guest_id), ""),
+    ) {
+        (Some(keep_index), Some(merge_index)) => (keep_index, merge_index),
+        _ => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+    let keep = wedding.guest_list[keep_index].clone();
+    let other = wedding.guest_list[merge_index].clone();
+    let merged = merge_guest_records(&keep, &other);
+
+    // Seats held before and after the merge
+    let seats = |guest: &Guest| {
+        if guest.rsvp_status == "confirmed" {
+            guest_headcount(guest)
+        } else {
+            0
+        }
+    };
+    let seats_before = seats(&keep) + seats(&other);
+    let seats_after = seats(&merged);
+
+    let mut updated_wedding = wedding.clone();
+    if seats_after > seats_before {
+        let extra_seats = seats_after - seats_before;
+        if extra_seats > updated_wedding.guest_count {
+            return Err(Message::BudgetExceeded(
+                "Not enough seats left for the merged guest".to_string(),
+            ));
+        }
+        updated_wedding.guest_count -= extra_seats;
+    }
+
+    updated_wedding.guest_list[keep_index] = merged.clone();
+    updated_wedding.guest_list.remove(merge_index);
+
+    // Invitations and waitlist places move to the guest we keep
+    for invitation in updated_wedding.invitations.iter_mut() {
+        if invitation.guest_id == Some(merge_guest_id) {
+            invitation.guest_id = Some(keep_guest_id);
+        }
+    }
+    if merged.rsvp_status == "waitlisted"
+        && !updated_wedding
+            .waitlist
+            .iter()
+            .any(|entry| entry.guest_id == keep_guest_id)
+    {
+        for entry in updated_wedding.waitlist.iter_mut() {
+            if entry.guest_id == merge_guest_id {
+                entry.guest_id = keep_guest_id;
+            }
+        }
+    }
+    if !has_lodging(&updated_wedding, keep_guest_id) {
+        for room in updated_wedding
+            .room_blocks
+            .iter_mut()
+            .flat_map(|block| block.claims.iter_mut())
+        {
+            for occupant in room
+                .occupants
+                .iter_mut()
+                .chain(room.invited_guests.iter_mut())
+            {
+                if *occupant == merge_guest_id {
+                    *occupant = keep_guest_id;
+                }
+            }
+        }
+    }
+    for run in updated_wedding.transport_runs.iter_mut() {
+        if run.passengers.contains(&merge_guest_id) && !run.passengers.contains(&keep_guest_id) {
+            run.passengers.push(keep_guest_id);
+        }
+    }
+    release_seats(
+        &mut updated_wedding,
+        seats_before.saturating_sub(seats_after),
+    );
+    release_unused_rooms(&mut updated_wedding);
+    release_unused_transport(&mut updated_wedding);
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        "Guests merged successfully".to_string(),
+        guest_view(&updated_wedding, &merged),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Set a waitlisted guest's priority
+#[ic_cdk::update]
+fn set_waitlist_priority(
+    wedding_id: u64,
+    guest_id: u64,
+    priority: u32,
+) -> Result<(WaitlistPosition, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can prioritise the waitlist".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    match updated_wedding
+        .waitlist
+        .iter_mut()
+        .find(|entry| entry.guest_id == guest_id)
+    {
+        Some(entry) => entry.priority = priority,
+        None => {
+            return Err(Message::Error("Guest is not on the waitlist".to_string()));
+        }
+    }
+
+    let position = match waitlist_position(&updated_wedding, guest_id) {
+        Some(position) => position,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        position,
+        Message::Success("Waitlist priority updated successfully".to_string()),
+    ))
+}
+
+/*
+ * Household Management
+ */
+
+// Create Household with its invited attendees
+#[ic_cdk::update]
+fn create_household(
+    payload: CreateHouseholdPayload,
+) -> Result<(String, Household, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can invite households".to_string(),
+        ));
+    }
+
+    // Validate the user input
+    if payload.name.is_empty() || payload.max_party_size == 0 {
+        return Err(Message::InvalidInput(
+            "Household name and a party size of at least 1 are required".to_string(),
+        ));
+    }
+
+    if payload.attendees.len() as u64 > payload.max_party_size {
+        return Err(Message::InvalidInput(format!(
+            "A household of {} can't list {} attendees",
+            payload.max_party_size,
+            payload.attendees.len()
+        )));
+    }
+
+    if payload
+        .attendees
+        .iter()
+        .any(|attendee| attendee.name.is_empty())
+    {
+        return Err(Message::InvalidInput(
+            "Every attendee needs a name".to_string(),
+        ));
+    }
+
+    // Attendee emails are optional, but must be valid and not clash with existing guests
+    let contact_email = validate_email(&payload.contact_email)?;
+    let mut attendee_emails = Vec::new();
+    for attendee in payload.attendees.iter() {
+        attendee_emails.push(validate_email(&attendee.guest_email)?);
+    }
+
+    let email_taken = attendee_emails.iter().any(|email| {
+        wedding
+            .guest_list
+            .iter()
+            .any(|guest| same_email(&guest.guest_email, email))
+    });
+
+    if email_taken {
+        return Err(Message::Error(
+            "An attendee is already on the guest list".to_string(),
+        ));
+    }
+
+    // Create the household and its attendees
+    let household = Household {
+        id: generate_uuid(),
+        name: payload.name.clone(),
+        contact_email,
+        max_party_size: payload.max_party_size,
+        responded: false,
+        principal: None,
+    };
+
+    let mut updated_wedding = wedding.clone();
+    for (attendee, guest_email) in payload.attendees.iter().zip(attendee_emails) {
+        updated_wedding.guest_list.push(Guest {
+            id: generate_uuid(),
+            household_id: Some(household.id),
+            name: attendee.name.clone(),
+            guest_email,
+            rsvp_status: "invited".to_string(),
+            age_group: attendee.age_group.clone(),
+            meal_choice: attendee.meal_choice.clone(),
+            dietary_restrictions: attendee.dietary_restrictions.clone(),
+            plus_one: false,
+            plus_one_name: None,
+            table_assignment: TableAssignment::Unassigned,
+            event_rsvps: Vec::new(),
+            principal: None,
+            rsvp_history: Vec::new(),
+            meal_option_id: None,
+            plus_one_meal_option_id: None,
+            allergens: Vec::new(),
+            check_in_token_hash: None,
+            checked_in_at: None,
+            tags: Vec::new(),
+            side: GuestSide::default(),
+            relationship: Relationship::default(),
+            address: "".to_string(),
+            erased: false,
+        });
+    }
+    updated_wedding.households.push(household.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Household created successfully".to_string(),
+        household,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Household RSVP, answering for every attendee at once
+#[ic_cdk::update]
+fn household_rsvp(payload: HouseholdRsvpPayload) -> Result<(String, Vec<Guest>, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    // Fetch the household from the wedding
+    let household_index = match wedding
+        .households
+        .iter()
+        .position(|household| household.id == payload.household_id)
+    {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Household not found".to_string()));
+        }
+    };
+
+    check_rsvp_open(&wedding)?;
+
+    // The household's bound principal can answer again; anyone else needs the code
+    let mut updated_wedding = wedding.clone();
+    if wedding.households[household_index].principal != Some(caller()) {
+        let code = match &payload.invitation_code {
+            Some(code) => code,
+            None => {
+                return Err(Message::UnauthorizedAction(
+                    "An invitation code is required to RSVP".to_string(),
+                ));
+            }
+        };
+
+        let invitation = redeem_invitation(&mut updated_wedding, code, caller())?;
+        if invitation.household_id != Some(payload.household_id) {
+            return Err(Message::UnauthorizedAction(
+                "This invitation is not for this household".to_string(),
+            ));
+        }
+    }
+    let household = updated_wedding.households[household_index].clone();
+
+    // Validate the user input
+    if payload
+        .attendees
+        .iter()
+        .any(|attendee| attendee.name.is_empty())
+    {
+        return Err(Message::InvalidInput(
+            "Every attendee needs a name".to_string(),
+        ));
+    }
+
+    let mut seats_freed = 0;
+    for attendee in payload.attendees.iter() {
+        let status = if attendee.attending {
+            "pending"
+        } else {
+            "declined"
+        };
+
+        match attendee.guest_id {
+            Some(guest_id) => {
+                let guest = updated_wedding
+                    .guest_list
+                    .iter_mut()
+                    .find(|guest| guest.id == guest_id && guest.household_id == Some(household.id));
+
+                let guest = match guest {
+                    Some(guest) => guest,
+                    None => {
+                        return Err(Message::Error(format!(
+                            "Guest with ID {} is not part of this household",
+                            guest_id
+                        )));
+                    }
+                };
+
+                guest.name = attendee.name.clone();
+                guest.age_group = attendee.age_group.clone();
+                guest.meal_choice = attendee.meal_choice.clone();
+                guest.dietary_restrictions = attendee.dietary_restrictions.clone();
+
+                // Confirmed and waitlisted attendees keep their place unless they now decline
+                if guest.rsvp_status == "confirmed" || guest.rsvp_status == "waitlisted" {
+                    if attendee.attending {
+                        continue;
+                    }
+                    if guest.rsvp_status == "confirmed" {
+                        seats_freed += guest_headcount(guest);
+                    }
+                }
+                guest.rsvp_status = status.to_string();
+            }
+            None => updated_wedding.guest_list.push(Guest {
+                id: generate_uuid(),
+                household_id: Some(household.id),
+                name: attendee.name.clone(),
+                guest_email: "".to_string(),
+                rsvp_status: status.to_string(),
+                age_group: attendee.age_group.clone(),
+                meal_choice: attendee.meal_choice.clone(),
+                dietary_restrictions: attendee.dietary_restrictions.clone(),
+                plus_one: false,
+                plus_one_name: None,
+                table_assignment: TableAssignment::Unassigned,
+                event_rsvps: Vec::new(),
+                principal: None,
+                rsvp_history: Vec::new(),
+                meal_option_id: None,
+                plus_one_meal_option_id: None,
+                allergens: Vec::new(),
+                check_in_token_hash: None,
+                checked_in_at: None,
+                tags: Vec::new(),
+                side: GuestSide::default(),
+                relationship: Relationship::default(),
+                address: "".to_string(),
+                erased: false,
+            }),
+        }
+    }
+
+    // Attendees the household didn't mention aren't coming
+    let listed: Vec<u64> = payload
+        .attendees
+        .iter()
+        .filter_map(|attendee| attendee.guest_id)
+        .collect();
+    for guest in updated_wedding.guest_list.iter_mut() {
+        if guest.household_id == Some(household.id)
+            && guest.rsvp_status == "invited"
+            && !listed.contains(&guest.id)
+        {
+            guest.rsvp_status = "declined".to_string();
+        }
+    }
+
+    let total_attending = updated_wedding
+        .guest_list
+        .iter()
+        .filter(|guest| guest.household_id == Some(household.id))
+        .filter(|guest| {
+            guest.rsvp_status == "pending"
+                || guest.rsvp_status == "waitlisted"
+                || guest.rsvp_status == "confirmed"
+        })
+        .count() as u64;
+
+    if total_attending > household.max_party_size {
+        return Err(Message::InvalidInput(format!(
+            "This invitation is for at most {} people",
+            household.max_party_size
+        )));
+    }
+
+    updated_wedding.households[household_index].responded = true;
+    release_seats(&mut updated_wedding, seats_freed);
+    release_unused_rooms(&mut updated_wedding);
+    release_unused_transport(&mut updated_wedding);
+    for guest in updated_wedding.guest_list.iter_mut() {
+        if guest.household_id == Some(household.id) {
+            record_rsvp_response(guest);
+        }
+    }
+
+    let members: Vec<Guest> = updated_wedding
+        .guest_list
+        .iter()
+        .filter(|guest| guest.household_id == Some(household.id))
+        .cloned()
+        .collect();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Household RSVP submitted successfully".to_string(),
+        members
+            .iter()
+            .map(|guest| guest_view(&updated_wedding, guest))
+            .collect(),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Get Household members
+#[ic_cdk::query]
+fn get_household_members(wedding_id: u64, household_id: u64) -> Result<Vec<Guest>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if !wedding
+                    .households
+                    .iter()
+                    .any(|household| household.id == household_id)
+                {
+                    return Err(Message::Error("Household not found".to_string()));
+                }
+
+                Ok(wedding
+                    .guest_list
+                    .iter()
+                    .filter(|guest| guest.household_id == Some(household_id))
+                    .map(|guest| guest_view(&wedding, guest))
+                    .collect())
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+/*
+ * Invitation Management
+ */
+
+// Add an invited guest to the guest list, ready for an invitation code
+#[ic_cdk::update]
+fn invite_guest(payload: InviteGuestPayload) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can invite guests".to_string(),
+        ));
+    }
+
+    // Validate the user input
+    if payload.name.is_empty() {
+        return Err(Message::InvalidInput("Guest name is required".to_string()));
+    }
+
+    let guest_email = validate_email(&payload.guest_email)?;
+    if wedding
+        .guest_list
+        .iter()
+        .any(|guest| same_email(&guest.guest_email, &guest_email))
+    {
+        return Err(Message::Error(
+            "Guest is already on the guest list".to_string(),
+        ));
+    }
+
+    let guest = Guest {
+        id: generate_uuid(),
+        household_id: None,
+        name: payload.name.clone(),
+        guest_email,
+        rsvp_status: "invited".to_string(),
+        age_group: AgeGroup::Adult,
+        meal_choice: "".to_string(),
+        dietary_restrictions: "".to_string(),
+        plus_one: false,
+        plus_one_name: None,
+        table_assignment: TableAssignment::Unassigned,
+        event_rsvps: Vec::new(),
+        principal: None,
+        rsvp_history: Vec::new(),
+        meal_option_id: None,
+        plus_one_meal_option_id: None,
+        allergens: Vec::new(),
+        check_in_token_hash: None,
+        checked_in_at: None,
+        tags: Vec::new(),
+        side: GuestSide::default(),
+        relationship: Relationship::default(),
+        address: "".to_string(),
+        erased: false,
+    };
+
+    // Update the wedding's guest list
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.guest_list.push(guest.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Guest invited successfully".to_string(),
+        guest_view(&updated_wedding, &guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Issue an invitation code for a guest or a household. Any earlier code for the
+// same invitee is revoked. The plaintext code is only ever returned here.
+#[ic_cdk::update]
+async fn issue_invitation(
+    payload: IssueInvitationPayload,
+) -> Result<(String, Invitation), Message> {
+    // Validate the wedding and the invitee before asking for randomness
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can issue invitations".to_string(),
+        ));
+    }
+
+    match (payload.guest_id, payload.household_id) {
+        (Some(guest_id), None) => {
+            if find_guest(&wedding, Some(guest_id), "").is_none() {
+                return Err(Message::Error("Guest not found".to_string()));
+            }
+        }
+        (None, Some(household_id)) => {
+            if !wedding
+                .households
+                .iter()
+                .any(|household| household.id == household_id)
+            {
+                return Err(Message::Error("Household not found".to_string()));
+            }
+        }
+        _ => {
+            return Err(Message::InvalidInput(
+                "Provide either a guest ID or a household ID".to_string(),
+            ));
+        }
+    }
+
+    let (random_bytes,) = raw_rand().await.map_err(|(_, error)| {
+        Message::Error(format!("Could not generate an invitation code: {}", error))
+    })?;
+    let code = random_code(&random_bytes, 10);
+
+    // Re-read the wedding, since it may have changed while we awaited
+    WEDDING_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        let mut wedding = match storage.get(&payload.wedding_id) {
+            Some(wedding) => wedding,
+            None => {
+                return Err(Message::WeddingNotFound(format!(
+                    "Wedding with ID {} not found",
+                    payload.wedding_id
+                )))
+            }
+        };
+
+        ensure_code_salt(&mut wedding, &random_bytes);
+        let invitation = Invitation {
+            id: generate_uuid(),
+            code_hash: hash_code(&wedding.code_salt, &code),
+            guest_id: payload.guest_id,
+            household_id: payload.household_id,
+            issued_at: current_timestamp(),
+            revoked: false,
+            redeemed_by: None,
+        };
+
+        for existing in wedding.invitations.iter_mut() {
+            if existing.guest_id == invitation.guest_id
+                && existing.household_id == invitation.household_id
+            {
+                existing.revoked = true;
+            }
+        }
+        wedding.invitations.push(invitation.clone());
+        storage.insert(payload.wedding_id, wedding);
+
+        Ok((code, invitation_view(invitation)))
+    })
+}
+
+// Revoke an invitation code
+#[ic_cdk::update]
+fn revoke_invitation(
+    wedding_id: u64,
+    invitation_id: u64,
+) -> Result<(Invitation, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let mut wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can revoke invitations".to_string(),
+        ));
+    }
+
+    let invitation = match wedding
+        .invitations
+        .iter_mut()
+        .find(|invitation| invitation.id == invitation_id)
+    {
+        Some(invitation) => {
+            invitation.revoked = true;
+            invitation.clone()
+        }
+        None => {
+            return Err(Message::Error("Invitation not found".to_string()));
+        }
+    };
+
+    // Whoever redeemed the revoked code loses access to the invitee
+    if let Some(redeemed_by) = invitation.redeemed_by {
+        for guest in wedding.guest_list.iter_mut() {
+            if Some(guest.id) == invitation.guest_id && guest.principal == Some(redeemed_by) {
+                guest.principal = None;
+            }
+        }
+        for household in wedding.households.iter_mut() {
+            if Some(household.id) == invitation.household_id
+                && household.principal == Some(redeemed_by)
+            {
+                household.principal = None;
+            }
+        }
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage.borrow_mut().insert(wedding_id, wedding);
+    });
+
+    Ok((
+        invitation_view(invitation),
+        Message::Success("Invitation revoked successfully".to_string()),
+    ))
+}
+
+// Get Invitations issued for a wedding
+#[ic_cdk::query]
+fn get_invitations(wedding_id: u64) -> Result<Vec<Invitation>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if !is_wedding_owner(&wedding) {
+                    return Err(Message::UnauthorizedAction(
+                        "Only the couple can view invitations".to_string(),
+                    ));
+                }
+
+                Ok(wedding
+                    .invitations
+                    .iter()
+                    .cloned()
+                    .map(invitation_view)
+                    .collect())
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+/*
+ * Wedding Event Management
+ */
+
+// Create a sub-event such as the rehearsal dinner or the brunch
+#[ic_cdk::update]
+fn create_wedding_event(
+    payload: CreateWeddingEventPayload,
+) -> Result<(String, WeddingEvent, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can create wedding events".to_string(),
+        ));
+    }
+
+    // Validate the user input
+    if payload.name.is_empty() || payload.time.is_empty() || payload.capacity == 0 {
+        return Err(Message::InvalidInput(
+            "Event name, time and a capacity of at least 1 are required".to_string(),
+        ));
+    }
+
+    let event = WeddingEvent {
+        id: generate_uuid(),
+        name: payload.name.clone(),
+        time: payload.time.clone(),
+        location: payload.location.clone(),
+        capacity: payload.capacity,
+    };
+
+    // Update the wedding's events
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.events.push(event.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Wedding event created successfully".to_string(),
+        event,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Invite a subset of guests to a sub-event
+#[ic_cdk::update]
+fn invite_guests_to_event(
+    payload: InviteGuestsToEventPayload,
+) -> Result<(String, WeddingEvent, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can invite guests to events".to_string(),
+        ));
+    }
+
+    // Fetch the event from the wedding
+    let event = match wedding
+        .events
+        .iter()
+        .find(|event| event.id == payload.event_id)
+    {
+        Some(event) => event.clone(),
+        None => {
+            return Err(Message::Error("Wedding event not found".to_string()));
+        }
+    };
+
+    // Every guest must already be on the guest list
+    if let Some(missing) = payload.guest_ids.iter().find(|guest_id| {
+        !wedding
+            .guest_list
+            .iter()
+            .any(|guest| guest.id == **guest_id)
+    }) {
+        return Err(Message::Error(format!(
+            "Guest with ID {} not found",
+            missing
+        )));
+    }
+
+    // Add the invitation to guests that don't have one yet
+    let mut updated_wedding = wedding.clone();
+    for guest in updated_wedding.guest_list.iter_mut() {
+        if payload.guest_ids.contains(&guest.id)
+            && !guest
+                .event_rsvps
+                .iter()
+                .any(|rsvp| rsvp.event_id == event.id)
+        {
+            guest.event_rsvps.push(EventRsvp {
+                event_id: event.id,
+                status: "invited".to_string(),
+                table_assignment: TableAssignment::Unassigned,
+            });
+        }
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Guests invited to the event successfully".to_string(),
+        event,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Guest RSVP to a single sub-event
+#[ic_cdk::update]
+fn event_rsvp(payload: EventRsvpPayload) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    // Fetch the event from the wedding
+    let event = match wedding
+        .events
+        .iter()
+        .find(|event| event.id == payload.event_id)
+    {
+        Some(event) => event.clone(),
+        None => {
+            return Err(Message::Error("Wedding event not found".to_string()));
+        }
+    };
+
+    // Fetch the guest from the wedding
+    let guest_index = match find_guest(&wedding, payload.guest_id, &payload.guest_email) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+    let guest = wedding.guest_list[guest_index].clone();
+
+    if !can_act_for_guest(&wedding, &guest) {
+        return Err(Message::UnauthorizedAction(
+            "Please redeem your invitation before responding to events".to_string(),
+        ));
+    }
+    check_rsvp_open(&wedding)?;
+
+    let rsvp_index = match guest
+        .event_rsvps
+        .iter()
+        .position(|rsvp| rsvp.event_id == event.id)
+    {
+        Some(index) => index,
+        None => {
+            return Err(Message::UnauthorizedAction(format!(
+                "Guest is not invited to {}",
+                event.name
+            )));
+        }
+    };
+
+    // Check the event's capacity before accepting
+    if payload.attending && guest.event_rsvps[rsvp_index].status != "confirmed" {
+        let attending: u64 = wedding
+            .guest_list
+            .iter()
+            .filter(|other| {
+                other
+                    .event_rsvps
+                    .iter()
+                    .any(|rsvp| rsvp.event_id == event.id && rsvp.status == "confirmed")
+            })
+            .map(guest_headcount)
+            .sum();
+
+        if attending + guest_headcount(&guest) > event.capacity {
+            return Err(Message::BudgetExceeded(format!(
+                "{} is at capacity",
+                event.name
+            )));
+        }
+    }
+
+    // Update the guest's RSVP for this event
+    let mut updated_guest = guest.clone();
+    updated_guest.event_rsvps[rsvp_index].status = if payload.attending {
+        "confirmed".to_string()
+    } else {
+        "declined".to_string()
+    };
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.guest_list[guest_index] = updated_guest.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Event RSVP submitted successfully".to_string(),
+        guest_view(&updated_wedding, &updated_guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Assign a guest to a table at a sub-event
+#[ic_cdk::update]
+fn assign_event_table(
+    payload: AssignEventTablePayload,
+) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can assign tables".to_string(),
+        ));
+    }
+
+    // Fetch the guest from the wedding
+    let guest_index = match find_guest(&wedding, Some(payload.guest_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    let mut updated_guest = wedding.guest_list[guest_index].clone();
+    let rsvp = updated_guest
+        .event_rsvps
+        .iter_mut()
+        .find(|rsvp| rsvp.event_id == payload.event_id && rsvp.status == "confirmed");
+
+    match rsvp {
+        Some(rsvp) => rsvp.table_assignment = payload.table_assignment.clone(),
+        None => {
+            return Err(Message::Error(
+                "Guest is not attending this event".to_string(),
+            ));
+        }
+    }
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.guest_list[guest_index] = updated_guest.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Event table assigned successfully".to_string(),
+        guest_view(&updated_wedding, &updated_guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Get Wedding Events
+#[ic_cdk::query]
+fn get_wedding_events(wedding_id: u64) -> Result<Vec<WeddingEvent>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if wedding.events.is_empty() {
+                    Err(Message::Error(
+                        "No events found for this wedding".to_string(),
+                    ))
+                } else {
+                    Ok(wedding.events.clone())
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Headcount for a single sub-event
+#[ic_cdk::query]
+fn get_event_headcount(wedding_id: u64, event_id: u64) -> Result<Headcount, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if !wedding.events.iter().any(|event| event.id == event_id) {
+                    return Err(Message::Error("Wedding event not found".to_string()));
+                }
+
+                Ok(tally_headcount(wedding.guest_list.iter().filter_map(
+                    |guest| {
+                        guest
+                            .event_rsvps
+                            .iter()
+                            .find(|rsvp| rsvp.event_id == event_id)
+                            .map(|rsvp| (guest, rsvp.status.as_str()))
+                    },
+                )))
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Seating for a single sub-event
+#[ic_cdk::query]
+fn get_event_seating(wedding_id: u64, event_id: u64) -> Result<Vec<EventAttendee>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if !wedding.events.iter().any(|event| event.id == event_id) {
+                    return Err(Message::Error("Wedding event not found".to_string()));
+                }
+
+                Ok(wedding
+                    .guest_list
+                    .iter()
+                    .filter_map(|guest| {
+                        guest
+                            .event_rsvps
+                            .iter()
+                            .find(|rsvp| rsvp.event_id == event_id && rsvp.status == "confirmed")
+                            .map(|rsvp| EventAttendee {
+                                guest_id: guest.id,
+                                name: guest.name.clone(),
+                                // Plus-one names are personal data, as in guest_view
+                                plus_one_name: if can_view_guest_details(&wedding)
+                                    || can_act_for_guest(&wedding, guest)
+                                {
+                                    guest.plus_one_name.clone()
+                                } else {
+                                    None
+                                },
+                                table_assignment: rsvp.table_assignment.clone(),
+                            })
+                    })
+                    .collect())
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Meal and dietary summary for the wedding, or for a single sub-event
+#[ic_cdk::query]
+fn get_dietary_summary(wedding_id: u64, event_id: Option<u64>) -> Result<DietarySummary, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => match event_id {
+                Some(event_id) => {
+                    if !wedding.events.iter().any(|event| event.id == event_id) {
+                        return Err(Message::Error("Wedding event not found".to_string()));
+                    }
+
+                    Ok(summarise_diets(wedding.guest_list.iter().filter(|guest| {
+                        guest
+                            .event_rsvps
+                            .iter()
+                            .any(|rsvp| rsvp.event_id == event_id && rsvp.status == "confirmed")
+                    })))
+                }
+                None => Ok(summarise_diets(
+                    wedding
+                        .guest_list
+                        .iter()
+                        .filter(|guest| guest.rsvp_status == "confirmed"),
+                )),
+            },
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+/*
+ * Guest Queries
+ */
+
+// Guest List
+#[ic_cdk::query]
+fn get_guest_list(wedding_id: u64) -> Result<Vec<Guest>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if wedding.guest_list.is_empty() {
+                    Err(Message::Error(
+                        "No guests found for this wedding".to_string(),
+                    ))
+                } else {
+                    Ok(wedding
+                        .guest_list
+                        .iter()
+                        .map(|guest| guest_view(&wedding, guest))
+                        .collect())
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Guest RSVP List
+#[ic_cdk::query]
+fn get_guest_details(wedding_id: u64, guest_email: String) -> Result<Guest, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                let guest = wedding
+                    .guest_list
+                    .iter()
+                    .find(|guest| same_email(&guest.guest_email, &guest_email));
+
+                match guest {
+                    Some(guest) => Ok(guest_view(&wedding, guest)),
+                    None => Err(Message::Error("Guest not found".to_string())),
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Guest RSVP Status
+#[ic_cdk::query]
+fn get_guest_rsvp_status(wedding_id: u64, guest_email: String) -> Result<String, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                let guest = wedding
+                    .guest_list
+                    .iter()
+                    .find(|guest| same_email(&guest.guest_email, &guest_email));
+
+                match guest {
+                    Some(guest) => Ok(guest.rsvp_status.clone()),
+                    None => Err(Message::Error("Guest not found".to_string())),
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Guest RSVP Count
+#[ic_cdk::query]
+fn get_guest_rsvp_count(wedding_id: u64) -> Result<u64, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => Ok(wedding.guest_list.len() as u64),
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Headcount of individual attendees, plus ones included
+#[ic_cdk::query]
+fn get_wedding_headcount(wedding_id: u64) -> Result<Headcount, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => Ok(tally_headcount(
+                wedding
+                    .guest_list
+                    .iter()
+                    .map(|guest| (guest, guest.rsvp_status.as_str())),
+            )),
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Guests that look like the same person, by email or similar name (couple only)
+#[ic_cdk::query]
+fn find_duplicate_guests(wedding_id: u64) -> Result<Vec<PossibleDuplicate>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple can look for duplicate guests".to_string(),
+                ));
+            }
+
+            let mut duplicates = Vec::new();
+            for (index, guest) in wedding.guest_list.iter().enumerate() {
+                for other in wedding.guest_list.iter().skip(index + 1) {
+                    if let Some(reason) = duplicate_reason(guest, other) {
+                        duplicates.push(PossibleDuplicate {
+                            guest_id: guest.id,
+                            guest_name: guest.name.clone(),
+                            other_guest_id: other.id,
+                            other_guest_name: other.name.clone(),
+                            reason,
+                        });
+                    }
+                }
+            }
+
+            Ok(duplicates)
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Filtered, paginated guest list (couple only)
+#[ic_cdk::query]
+fn search_guests(filter: GuestFilterPayload) -> Result<GuestPage, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&filter.wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple can search the guest list".to_string(),
+                ));
+            }
+
+            let limit = if filter.limit == 0 {
+                MAX_GUEST_PAGE_SIZE
+            } else {
+                filter.limit.min(MAX_GUEST_PAGE_SIZE)
+            };
+            let matching: Vec<&Guest> = wedding
+                .guest_list
+                .iter()
+                .filter(|guest| guest_matches_filter(guest, &filter))
+                .collect();
+
+            Ok(GuestPage {
+                total: matching.len() as u64,
+                guests: matching
+                    .into_iter()
+                    .skip(filter.offset as usize)
+                    .take(limit as usize)
+                    .map(|guest| guest_view(&wedding, guest))
+                    .collect(),
+                offset: filter.offset,
+                limit,
+            })
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Headcounts by tag, side and relationship, e.g. confirmed colleagues (couple only)
+#[ic_cdk::query]
+fn get_guest_breakdown(wedding_id: u64) -> Result<GuestBreakdown, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple can view the guest breakdown".to_string(),
+                ));
+            }
+
+            let group = |label: String, belongs: &dyn Fn(&Guest) -> bool| GroupHeadcount {
+                label,
+                headcount: tally_headcount(
+                    wedding
+                        .guest_list
+                        .iter()
+                        .filter(|guest| belongs(guest))
+                        .map(|guest| (guest, guest.rsvp_status.as_str())),
+                ),
+            };
+
+            let mut tags: Vec<String> = Vec::new();
+            for tag in wedding
+                .guest_list
+                .iter()
+                .flat_map(|guest| guest.tags.iter())
+            {
+                if !tags
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(tag))
+                {
+                    tags.push(tag.clone());
+                }
+            }
+
+            Ok(GuestBreakdown {
+                tags: tags
+                    .into_iter()
+                    .map(|tag| {
+                        let belongs = |guest: &Guest| {
+                            guest
+                                .tags
+                                .iter()
+                                .any(|guest_tag| guest_tag.eq_ignore_ascii_case(&tag))
+                        };
+                        group(tag.clone(), &belongs)
+                    })
+                    .collect(),
+                sides: [GuestSide::PartnerA, GuestSide::PartnerB, GuestSide::Mutual]
+                    .into_iter()
+                    .map(|side| group(format!("{:?}", side), &|guest: &Guest| guest.side == side))
+                    .collect(),
+                relationships: [
+                    Relationship::Family,
+                    Relationship::Friend,
+                    Relationship::Colleague,
+                    Relationship::Other,
+                ]
+                .into_iter()
+                .map(|relationship| {
+                    group(format!("{:?}", relationship), &|guest: &Guest| {
+                        guest.relationship == relationship
+                    })
+                })
+                .collect(),
+            })
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get the waitlist in promotion order (couple only)
+#[ic_cdk::query]
+fn get_waitlist(wedding_id: u64) -> Result<Vec<WaitlistPosition>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple can view the waitlist".to_string(),
+                ));
+            }
+            Ok(waitlist_order(&wedding)
+                .iter()
+                .filter_map(|entry| waitlist_position(&wedding, entry.guest_id))
+                .collect())
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get a guest's waitlist position (the guest or the couple)
+#[ic_cdk::query]
+fn get_waitlist_position(wedding_id: u64, guest_id: u64) -> Result<WaitlistPosition, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            let guest = match wedding.guest_list.iter().find(|guest| guest.id == guest_id) {
+                Some(guest) => guest,
+                None => return Err(Message::Error("Guest not found".to_string())),
+            };
+            if !can_act_for_guest(&wedding, guest) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the guest or the couple can view this waitlist position".to_string(),
+                ));
+            }
+            waitlist_position(&wedding, guest_id)
+                .ok_or(Message::Error("Guest is not on the waitlist".to_string()))
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get the RSVP reminders queued for guests (couple only)
+#[ic_cdk::query]
+fn get_rsvp_reminders(wedding_id: u64) -> Result<Vec<RsvpReminder>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple can view RSVP reminders".to_string(),
+                ));
+            }
+            Ok(wedding.rsvp_reminders.clone())
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Guest Data Privacy
+ */
+
+// Choose the planners who can see guest contact details
+#[ic_cdk::update]
+fn set_planners(wedding_id: u64, planners: Vec<Principal>) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can choose planners".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.planners = planners;
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("Planners updated successfully".to_string()),
+    ))
+}
+
+// Erase a guest's personal data, at the guest's or the couple's request
+#[ic_cdk::update]
+fn erase_guest_data(wedding_id: u64, guest_id: u64) -> Result<(String, Guest), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    let guest_index = match find_guest(&wedding, Some(guest_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+    let guest = wedding.guest_list[guest_index].clone();
+
+    if !can_act_for_guest(&wedding, &guest) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest or the couple can erase this guest's data".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    scrub_gift_records(&mut updated_wedding, &guest.name, &guest.guest_email);
+    scrub_guest(&mut updated_wedding.guest_list[guest_index]);
+
+    // The guest's invitation and reminders go with their data
+    for invitation in updated_wedding.invitations.iter_mut() {
+        if invitation.guest_id == Some(guest_id) {
+            invitation.revoked = true;
+            invitation.redeemed_by = None;
+        }
+    }
+    updated_wedding
+        .rsvp_reminders
+        .retain(|reminder| reminder.guest_id != guest_id);
+
+    let erased_guest = guest_view(&updated_wedding, &updated_wedding.guest_list[guest_index]);
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage.borrow_mut().insert(wedding_id, updated_wedding);
+    });
+
+    Ok(("Guest data erased successfully".to_string(), erased_guest))
+}
+
+// Erase all guest personal data once the retention period after the wedding is over
+#[ic_cdk::update]
+fn erase_all_guest_data(wedding_id: u64) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can erase all guest data".to_string(),
+        ));
+    }
+
+    if wedding.status != "completed" {
+        return Err(Message::Error(
+            "Guest data can only be erased once the wedding is marked completed".to_string(),
+        ));
+    }
+
+    let retention_over = parse_date(&wedding.date)
+        .and_then(|date| date.checked_add_days(chrono::Days::new(GUEST_DATA_RETENTION_DAYS)))
+        .is_some_and(|erase_after| erase_after < current_datetime().date());
+    if !retention_over {
+        return Err(Message::InvalidDate(format!(
+            "Guest data can be erased {} days after the wedding",
+            GUEST_DATA_RETENTION_DAYS
+        )));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    for guest in updated_wedding.guest_list.iter_mut() {
+        scrub_guest(guest);
+    }
+    for household in updated_wedding.households.iter_mut() {
+        household.name = "Erased household".to_string();
+        household.contact_email = "".to_string();
+        household.principal = None;
+    }
+    for gift in updated_wedding.gifts.iter_mut() {
+        gift.giver = "".to_string();
+        gift.guest_email = None;
+    }
+    for item in updated_wedding.registry.iter_mut() {
+        item.purchased_by = "".to_string();
+        item.reserved_by = None;
+        for purchase in item.purchases.iter_mut() {
+            purchase.purchased_by = "".to_string();
+        }
+        for contribution in item.contributions.iter_mut() {
+            contribution.contributor = "".to_string();
+        }
+    }
+    updated_wedding.invitations.clear();
+    updated_wedding.rsvp_reminders.clear();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("All guest data erased successfully".to_string()),
+    ))
+}
+
+/*
+ * Guest Import and Export
+ */
+
+// Import a chunk of a guest list; bad or duplicate rows are reported and skipped
+#[ic_cdk::update]
+fn import_guests(payload: ImportGuestsPayload) -> Result<(ImportReport, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can import guests".to_string(),
+        ));
+    }
+
+    let rows = read_guest_rows(&payload.format, &payload.data)?;
+
+    let mut updated_wedding = wedding.clone();
+    let mut report = ImportReport::default();
+    for (index, row) in rows.iter().enumerate() {
+        let mut reject = |message: String| {
+            report.errors.push(ImportRowError {
+                row: index as u64 + 1,
+                name: row.name.clone(),
+                message,
+            })
+        };
+
+        let guest = match guest_from_import_row(row) {
+            Ok(guest) => guest,
+            Err(message) => {
+                reject(message);
+                continue;
+            }
+        };
+
+        // Guests match on email, or on name when neither has an email
+        let duplicate = updated_wedding.guest_list.iter().any(|existing| {
+            if guest.guest_email.is_empty() || existing.guest_email.is_empty() {
+                guest.guest_email.is_empty()
+                    && existing.guest_email.is_empty()
+                    && existing.name.eq_ignore_ascii_case(&guest.name)
+            } else {
+                same_email(&existing.guest_email, &guest.guest_email)
+            }
+        });
+        if duplicate {
+            reject("Guest is already on the guest list".to_string());
+            report.duplicates += 1;
+            continue;
+        }
+
+        updated_wedding.guest_list.push(guest);
+        report.imported += 1;
+    }
+
+    let size = Encode!(&updated_wedding)
+        .map(|bytes| bytes.len())
+        .unwrap_or(0);
+    if size > Wedding::MAX_SIZE as usize {
+        return Err(Message::Error(
+            "The guest list is too large to store; import fewer guests".to_string(),
+        ));
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    let message = format!(
+        "Imported {} guests, {} rows skipped",
+        report.imported,
+        report.errors.len()
+    );
+    Ok((report, Message::Success(message)))
+}
+
+// Export the guest list as CSV or JSON (couple only)
+#[ic_cdk::query]
+fn export_guests(wedding_id: u64, format: GuestListFormat) -> Result<String, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple can export the guest list".to_string(),
+                ));
+            }
+
+            let rows: Vec<GuestExportRow> = wedding
+                .guest_list
+                .iter()
+                .map(|guest| GuestExportRow {
+                    name: guest.name.clone(),
+                    email: guest.guest_email.clone(),
+                    rsvp_status: guest.rsvp_status.clone(),
+                    plus_one: guest.plus_one,
+                    plus_one_name: guest.plus_one_name.clone().unwrap_or_default(),
+                    meal_choice: guest.meal_choice.clone(),
+                    meal_option: wedding
+                        .meal_options
+                        .iter()
+                        .find(|option| Some(option.id) == guest.meal_option_id)
+                        .map(|option| option.name.clone())
+                        .unwrap_or_default(),
+                    dietary_restrictions: guest.dietary_restrictions.clone(),
+                    allergens: guest
+                        .allergens
+                        .iter()
+                        .map(|allergen| format!("{:?}", allergen))
+                        .collect(),
+                    table: format!("{:?}", guest.table_assignment),
+                    age_group: format!("{:?}", guest.age_group),
+                    side: format!("{:?}", guest.side),
+                    relationship: format!("{:?}", guest.relationship),
+                    tags: guest.tags.clone(),
+                    address: guest.address.clone(),
+                })
+                .collect();
+
+            match format {
+                GuestListFormat::Json => serde_json::to_string(&rows)
+                    .map_err(|error| Message::Error(format!("Could not export guests: {}", error))),
+                GuestListFormat::Csv => {
+                    let mut csv = "name,email,rsvp_status,plus_one,plus_one_name,meal_choice,\
+                                   meal_option,dietary_restrictions,allergens,table,age_group,\
+                                   side,relationship,tags,address\n"
+                        .to_string();
+                    for row in rows.iter() {
+                        let fields = [
+                            row.name.clone(),
+                            row.email.clone(),
+                            row.rsvp_status.clone(),
+                            row.plus_one.to_string(),
+                            row.plus_one_name.clone(),
+                            row.meal_choice.clone(),
+                            row.meal_option.clone(),
+                            row.dietary_restrictions.clone(),
+                            row.allergens.join(";"),
+                            row.table.clone(),
+                            row.age_group.clone(),
+                            row.side.clone(),
+                            row.relationship.clone(),
+                            row.tags.join(";"),
+                            row.address.clone(),
+                        ];
+                        let fields: Vec<String> =
+                            fields.iter().map(|field| csv_field(field)).collect();
+                        csv.push_str(&fields.join(","));
+                        csv.push('\n');
+                    }
+                    Ok(csv)
+                }
+            }
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Catering
+ */
+
+// Add a meal option (the couple or their booked caterer)
+#[ic_cdk::update]
+fn add_meal_option(payload: AddMealOptionPayload) -> Result<(MealOption, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    let offered_by = caller_booked_vendor(&wedding, Category::Catering);
+    if !is_wedding_owner(&wedding) && offered_by.is_none() {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their booked caterer can add meal options".to_string(),
+        ));
+    }
+
+    if catering_locked(&wedding) {
+        return Err(Message::InvalidDate(
+            "The final headcount has been locked".to_string(),
+        ));
+    }
+
+    if payload.name.trim().is_empty() {
+        return Err(Message::InvalidInput(
+            "Meal option name is required".to_string(),
+        ));
+    }
+
+    let meal_option = MealOption {
+        id: generate_uuid(),
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        allergens: payload.allergens,
+        offered_by,
+    };
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.meal_options.push(meal_option.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        meal_option,
+        Message::Success("Meal option added successfully".to_string()),
+    ))
+}
+
+// Remove a meal option; guests who chose it will need to choose again
+#[ic_cdk::update]
+fn remove_meal_option(
+    wedding_id: u64,
+    meal_option_id: u64,
+) -> Result<(MealOption, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && caller_booked_vendor(&wedding, Category::Catering).is_none() {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their booked caterer can remove meal options".to_string(),
+        ));
+    }
+
+    if catering_locked(&wedding) {
+        return Err(Message::InvalidDate(
+            "The final headcount has been locked".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let meal_option = match updated_wedding
+        .meal_options
+        .iter()
+        .position(|option| option.id == meal_option_id)
+    {
+        Some(index) => updated_wedding.meal_options.remove(index),
+        None => {
+            return Err(Message::Error("Meal option not found".to_string()));
+        }
+    };
+
+    for guest in updated_wedding.guest_list.iter_mut() {
+        if guest.meal_option_id == Some(meal_option_id) {
+            guest.meal_option_id = None;
+        }
+        if guest.plus_one_meal_option_id == Some(meal_option_id) {
+            guest.plus_one_meal_option_id = None;
+        }
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        meal_option,
+        Message::Success("Meal option removed successfully".to_string()),
+    ))
+}
+
+// Choose meals and declare allergens for a guest and their plus one
+#[ic_cdk::update]
+fn set_guest_meal(payload: SetGuestMealPayload) -> Result<(String, Guest, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    let guest_index = match find_guest(&wedding, Some(payload.guest_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    if !can_act_for_guest(&wedding, &wedding.guest_list[guest_index]) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest or the couple can choose this guest's meal".to_string(),
+        ));
+    }
+
+    if catering_locked(&wedding) {
+        return Err(Message::InvalidDate(
+            "The final headcount has been locked; please contact the couple".to_string(),
+        ));
+    }
+
+    let unknown_option = [payload.meal_option_id, payload.plus_one_meal_option_id]
+        .iter()
+        .flatten()
+        .any(|id| !wedding.meal_options.iter().any(|option| option.id == *id));
+    if unknown_option {
+        return Err(Message::InvalidInput("Meal option not found".to_string()));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let guest = &mut updated_wedding.guest_list[guest_index];
+    if payload.plus_one_meal_option_id.is_some() && !guest.plus_one {
+        return Err(Message::InvalidInput(
+            "This guest is not bringing a plus one".to_string(),
+        ));
+    }
+    guest.meal_option_id = payload.meal_option_id;
+    guest.plus_one_meal_option_id = payload.plus_one_meal_option_id;
+    guest.allergens = payload.allergens;
+    let guest = guest.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        "Meal selection saved successfully".to_string(),
+        guest_view(&updated_wedding, &guest),
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Set or clear the date after which meals and headcount are final
+#[ic_cdk::update]
+fn set_catering_lock_date(
+    wedding_id: u64,
+    lock_date: Option<String>,
+) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can set the catering lock date".to_string(),
+        ));
+    }
+
+    if let Some(date) = &lock_date {
+        if parse_date(date).is_none() {
+            return Err(Message::InvalidDate(
+                "Lock date must be in YYYY-MM-DD format".to_string(),
+            ));
+        }
+    }
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.catering_lock_date = lock_date;
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("Catering lock date updated successfully".to_string()),
+    ))
+}
+
+// Get the meal options for a wedding
+#[ic_cdk::query]
+fn get_meal_options(wedding_id: u64) -> Result<Vec<MealOption>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => Ok(wedding.meal_options.clone()),
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get the catering report (the couple or their booked caterer)
+#[ic_cdk::query]
+fn get_catering_report(wedding_id: u64) -> Result<CateringReport, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding)
+                && caller_booked_vendor(&wedding, Category::Catering).is_none()
+            {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple or their booked caterer can view the catering report"
+                        .to_string(),
+                ));
+            }
+            Ok(build_catering_report(&wedding))
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Accommodation
+ */
+
+// Add a hotel room block (the couple or their planner)
+#[ic_cdk::update]
+fn add_room_block(payload: AddRoomBlockPayload) -> Result<(RoomBlock, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !can_view_guest_details(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can add room blocks".to_string(),
+        ));
+    }
+
+    // Validate the user input
+    if payload.property.trim().is_empty() || payload.room_types.is_empty() {
+        return Err(Message::InvalidInput(
+            "A property and at least one room type are required".to_string(),
+        ));
+    }
+    if parse_date(&payload.booking_cutoff).is_none() {
+        return Err(Message::InvalidDate(
+            "Booking cutoff must be in YYYY-MM-DD format".to_string(),
+        ));
+    }
+    let invalid_room_type = payload.room_types.iter().any(|room_type| {
+        room_type.name.trim().is_empty() || room_type.capacity == 0 || room_type.count == 0
+    });
+    if invalid_room_type {
+        return Err(Message::InvalidInput(
+            "Every room type needs a name, a capacity and a number of rooms".to_string(),
+        ));
+    }
+
+    let room_block = RoomBlock {
+        id: generate_uuid(),
+        property: payload.property.trim().to_string(),
+        address: payload.address.trim().to_string(),
+        room_types: payload
+            .room_types
+            .iter()
+            .map(|room_type| RoomType {
+                id: generate_uuid(),
+                name: room_type.name.trim().to_string(),
+                capacity: room_type.capacity,
+                count: room_type.count,
+                nightly_rate: room_type.nightly_rate,
+            })
+            .collect(),
+        booking_cutoff: payload.booking_cutoff.clone(),
+        booking_code: payload.booking_code.trim().to_string(),
+        claims: Vec::new(),
+    };
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.room_blocks.push(room_block.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        room_block,
+        Message::Success("Room block added successfully".to_string()),
+    ))
+}
+
+// Remove a room block; guests staying in it will need another room
+#[ic_cdk::update]
+fn remove_room_block(wedding_id: u64, block_id: u64) -> Result<(RoomBlock, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !can_view_guest_details(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can remove room blocks".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let room_block = match updated_wedding
+        .room_blocks
+        .iter()
+        .position(|block| block.id == block_id)
+    {
+        Some(index) => updated_wedding.room_blocks.remove(index),
+        None => {
+            return Err(Message::Error("Room block not found".to_string()));
+        }
+    };
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        room_block,
+        Message::Success("Room block removed successfully".to_string()),
+    ))
+}
+
+// Claim a room in a block for a guest, or join a room another guest claimed
+#[ic_cdk::update]
+fn claim_room(payload: ClaimRoomPayload) -> Result<(RoomClaim, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    let guest_index = match find_guest(&wedding, Some(payload.guest_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    if !can_act_for_guest(&wedding, &wedding.guest_list[guest_index]) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest or the couple can claim a room for this guest".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let room = claim_room_for(&mut updated_wedding, payload.guest_id, &payload.room)?;
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        room,
+        Message::Success("Room claimed successfully".to_string()),
+    ))
+}
+
+// Give up a guest's room
+#[ic_cdk::update]
+fn release_room(wedding_id: u64, guest_id: u64) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    let guest_index = match find_guest(&wedding, Some(guest_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    if !can_act_for_guest(&wedding, &wedding.guest_list[guest_index]) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest or the couple can give up this guest's room".to_string(),
+        ));
+    }
+
+    if !has_lodging(&wedding, guest_id) {
+        return Err(Message::Error(
+            "This guest hasn't claimed a room".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    vacate_room(&mut updated_wedding, guest_id);
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("Room released successfully".to_string()),
+    ))
+}
+
+// Invite a guest to share a room; only then can they join it
+#[ic_cdk::update]
+fn invite_to_room(
+    wedding_id: u64,
+    room_id: u64,
+    guest_id: u64,
+) -> Result<(RoomClaim, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if find_guest(&wedding, Some(guest_id), "").is_none() {
+        return Err(Message::Error("Guest not found".to_string()));
+    }
+
+    let room = match wedding
+        .room_blocks
+        .iter()
+        .flat_map(|block| block.claims.iter())
+        .find(|room| room.id == room_id)
+    {
+        Some(room) => room.clone(),
+        None => {
+            return Err(Message::Error("Room not found".to_string()));
+        }
+    };
+
+    let acts_for_occupant = wedding
+        .guest_list
+        .iter()
+        .any(|guest| room.occupants.contains(&guest.id) && can_act_for_guest(&wedding, guest));
+    if !acts_for_occupant {
+        return Err(Message::UnauthorizedAction(
+            "Only someone staying in this room, or the couple, can invite a guest to it"
+                .to_string(),
+        ));
+    }
+
+    if room.occupants.contains(&guest_id) {
+        return Err(Message::Error(
+            "This guest is already staying in the room".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let mut updated_room = room.clone();
+    for claim in updated_wedding
+        .room_blocks
+        .iter_mut()
+        .flat_map(|block| block.claims.iter_mut())
+        .filter(|claim| claim.id == room_id)
+    {
+        if !claim.invited_guests.contains(&guest_id) {
+            claim.invited_guests.push(guest_id);
+        }
+        updated_room = claim.clone();
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        updated_room,
+        Message::Success("Guest invited to the room successfully".to_string()),
+    ))
+}
+
+// Get the room blocks for a wedding, with their booking codes and claimed rooms
+#[ic_cdk::query]
+fn get_room_blocks(wedding_id: u64) -> Result<Vec<RoomBlock>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => Ok(room_blocks_view(&wedding)),
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get the lodging report (the couple or their planner)
+#[ic_cdk::query]
+fn get_lodging_report(wedding_id: u64) -> Result<LodgingReport, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !can_view_guest_details(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple or their planner can view the lodging report".to_string(),
+                ));
+            }
+            Ok(build_lodging_report(&wedding))
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Transport
+ */
+
+// Add a transport run operated by a booked Transport vendor (the couple or their planner)
+#[ic_cdk::update]
+fn add_transport_run(payload: AddTransportRunPayload) -> Result<(TransportRun, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !can_view_guest_details(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can plan transport".to_string(),
+        ));
+    }
+
+    // Validate the user input
+    if payload.route.trim().is_empty()
+        || payload.departure_time.trim().is_empty()
+        || payload.vehicle_capacity == 0
+    {
+        return Err(Message::InvalidInput(
+            "Route, departure time and vehicle capacity are required".to_string(),
+        ));
+    }
+
+    // The run must be operated by a Transport vendor booked for this wedding
+    let booked = wedding.vendors.iter().any(|booking| {
+        booking.vendor_id == payload.vendor_id
+            && (booking.status == "confirmed" || booking.status == "paid")
+    });
+    let is_transport = VENDOR_STORAGE.with(|storage| {
+        storage
+            .borrow()
+            .get(&payload.vendor_id)
+            .is_some_and(|vendor| vendor.category == Category::Transport)
+    });
+    if !booked || !is_transport {
+        return Err(Message::VendorNotFound(
+            "No booked Transport vendor with this ID".to_string(),
+        ));
+    }
+
+    let transport_run = TransportRun {
+        id: generate_uuid(),
+        route: payload.route.trim().to_string(),
+        departure_time: payload.departure_time.trim().to_string(),
+        vehicle_capacity: payload.vehicle_capacity,
+        vendor_id: payload.vendor_id,
+        passengers: Vec::new(),
+    };
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.transport_runs.push(transport_run.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        transport_run,
+        Message::Success("Transport run added successfully".to_string()),
+    ))
+}
+
+// Remove a transport run
+#[ic_cdk::update]
+fn remove_transport_run(wedding_id: u64, run_id: u64) -> Result<(TransportRun, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !can_view_guest_details(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can remove transport runs".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let transport_run = match updated_wedding
+        .transport_runs
+        .iter()
+        .position(|run| run.id == run_id)
+    {
+        Some(index) => updated_wedding.transport_runs.remove(index),
+        None => {
+            return Err(Message::Error("Transport run not found".to_string()));
+        }
+    };
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        transport_run,
+        Message::Success("Transport run removed successfully".to_string()),
+    ))
+}
+
+// Put guests, or a whole household, on a transport run
+#[ic_cdk::update]
+fn assign_transport(payload: AssignTransportPayload) -> Result<(TransportRun, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !can_view_guest_details(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can assign transport".to_string(),
+        ));
+    }
+
+    let run_index = match wedding
+        .transport_runs
+        .iter()
+        .position(|run| run.id == payload.run_id)
+    {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Transport run not found".to_string()));
+        }
+    };
+
+    let mut guest_ids = payload.guest_ids.clone();
+    if let Some(household_id) = payload.household_id {
+        if !wedding
+            .households
+            .iter()
+            .any(|household| household.id == household_id)
+        {
+            return Err(Message::Error("Household not found".to_string()));
+        }
+        guest_ids.extend(
+            wedding
+                .guest_list
+                .iter()
+                .filter(|guest| guest.household_id == Some(household_id))
+                .filter(|guest| {
+                    guest.rsvp_status == "pending"
+                        || guest.rsvp_status == "confirmed"
+                        || guest.rsvp_status == "waitlisted"
+                })
+                .map(|guest| guest.id),
+        );
+    }
+
+    let mut updated_wedding = wedding.clone();
+    for guest_id in guest_ids {
+        let guest = match find_guest(&wedding, Some(guest_id), "") {
+            Some(index) => &wedding.guest_list[index],
+            None => {
+                return Err(Message::Error(format!(
+                    "Guest with ID {} not found",
+                    guest_id
+                )));
+            }
+        };
+        if guest.rsvp_status == "declined" || guest.rsvp_status == "no-response" {
+            return Err(Message::Error(format!("{} is not attending", guest.name)));
+        }
+        let run = &mut updated_wedding.transport_runs[run_index];
+        if !run.passengers.contains(&guest_id) {
+            run.passengers.push(guest_id);
+        }
+    }
+
+    let transport_run = updated_wedding.transport_runs[run_index].clone();
+    let seats_taken = transport_seats_taken(&updated_wedding, &transport_run);
+    if seats_taken > transport_run.vehicle_capacity {
+        return Err(Message::InvalidInput(format!(
+            "This run seats {}; {} seats would be taken",
+            transport_run.vehicle_capacity, seats_taken
+        )));
+    }
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        transport_run,
+        Message::Success("Transport assigned successfully".to_string()),
+    ))
+}
+
+// Take a guest off a transport run
+#[ic_cdk::update]
+fn unassign_transport(
+    wedding_id: u64,
+    run_id: u64,
+    guest_id: u64,
+) -> Result<(TransportRun, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !can_view_guest_details(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can assign transport".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let run = match updated_wedding
+        .transport_runs
+        .iter_mut()
+        .find(|run| run.id == run_id)
+    {
+        Some(run) => run,
+        None => {
+            return Err(Message::Error("Transport run not found".to_string()));
+        }
+    };
+    if !run.passengers.contains(&guest_id) {
+        return Err(Message::Error(
+            "This guest is not on this transport run".to_string(),
+        ));
+    }
+    run.passengers.retain(|passenger| *passenger != guest_id);
+    let transport_run = run.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        transport_run,
+        Message::Success("Guest removed from the transport run".to_string()),
+    ))
+}
+
+// Get the transport runs for a wedding
+#[ic_cdk::query]
+fn get_transport_runs(wedding_id: u64) -> Result<Vec<TransportRun>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => Ok(wedding.transport_runs.clone()),
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get passenger manifests; a booked transport vendor sees the runs they operate
+#[ic_cdk::query]
+fn get_transport_manifest(wedding_id: u64) -> Result<Vec<TransportManifest>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            let vendor_id = caller_booked_vendor(&wedding, Category::Transport);
+            if !can_view_guest_details(&wedding) && vendor_id.is_none() {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple, their planner or their booked transport vendor can view manifests"
+                        .to_string(),
+                ));
+            }
+            Ok(wedding
+                .transport_runs
+                .iter()
+                .filter(|run| can_view_guest_details(&wedding) || Some(run.vendor_id) == vendor_id)
+                .map(|run| build_transport_manifest(&wedding, run))
+                .collect())
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Day-of Check-in
+ */
+
+// Issue a confirmed guest's check-in token, to be shown as a QR code; replaces any earlier token
+#[ic_cdk::update]
+async fn issue_check_in_token(wedding_id: u64, guest_id: u64) -> Result<(String, Guest), Message> {
+    // Validate the wedding and the guest before asking for randomness
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    let guest = match find_guest(&wedding, Some(guest_id), "") {
+        Some(index) => wedding.guest_list[index].clone(),
+        None => {
+            return Err(Message::Error("Guest not found".to_string()));
+        }
+    };
+
+    if !can_act_for_guest(&wedding, &guest) {
+        return Err(Message::UnauthorizedAction(
+            "Only the guest or the couple can get a check-in token".to_string(),
+        ));
+    }
+
+    if guest.rsvp_status != "confirmed" {
+        return Err(Message::Error(
+            "Only confirmed guests can get a check-in token".to_string(),
+        ));
+    }
+
+    let (random_bytes,) = raw_rand().await.map_err(|(_, error)| {
+        Message::Error(format!("Could not generate a check-in token: {}", error))
+    })?;
+    let token = random_code(&random_bytes, 16);
+
+    // Re-read the wedding, since it may have changed while we awaited
+    WEDDING_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        let mut wedding = match storage.get(&wedding_id) {
+            Some(wedding) => wedding,
+            None => {
+                return Err(Message::WeddingNotFound(format!(
+                    "Wedding with ID {} not found",
+                    wedding_id
+                )))
+            }
+        };
+
+        ensure_code_salt(&mut wedding, &random_bytes);
+        let token_hash = hash_code(&wedding.code_salt, &token);
+        let guest = match wedding
+            .guest_list
+            .iter_mut()
+            .find(|guest| guest.id == guest_id)
+        {
+            Some(guest) => guest,
+            None => return Err(Message::Error("Guest not found".to_string())),
+        };
+        if guest.checked_in_at.is_some() {
+            return Err(Message::Error("Guest has already checked in".to_string()));
+        }
+        guest.check_in_token_hash = Some(token_hash);
+        let guest = guest.clone();
+        let guest = guest_view(&wedding, &guest);
+        storage.insert(wedding_id, wedding);
+
+        Ok((token, guest))
+    })
+}
+
+// Allow venue staff to check guests in
+#[ic_cdk::update]
+fn set_check_in_staff(
+    wedding_id: u64,
+    staff: Vec<Principal>,
+) -> Result<(Wedding, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can choose check-in staff".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.check_in_staff = staff;
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("Check-in staff updated successfully".to_string()),
+    ))
+}
+
+// Check a guest in with their token; each token works once
+#[ic_cdk::update]
+fn check_in_guest(wedding_id: u64, token: String) -> Result<(String, Guest), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !can_check_in_guests(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple, planner or venue staff can check guests in".to_string(),
+        ));
+    }
+
+    let token_hash = hash_code(&wedding.code_salt, &token);
+    let guest_index = match wedding
+        .guest_list
+        .iter()
+        .position(|guest| guest.check_in_token_hash.as_deref() == Some(token_hash.as_str()))
+    {
+        Some(index) => index,
+        None => {
+            return Err(Message::UnauthorizedAction(
+                "Invalid check-in token".to_string(),
+            ));
+        }
+    };
+
+    let mut updated_wedding = wedding.clone();
+    let guest = &mut updated_wedding.guest_list[guest_index];
+    if let Some(checked_in_at) = &guest.checked_in_at {
+        return Err(Message::Error(format!(
+            "This token was already used to check in at {}",
+            checked_in_at
+        )));
+    }
+    if guest.rsvp_status != "confirmed" {
+        return Err(Message::Error(
+            "This guest's RSVP is no longer confirmed".to_string(),
+        ));
+    }
+    guest.checked_in_at = Some(current_timestamp());
+    let guest = guest.clone();
+    let guest = guest_view(&updated_wedding, &guest);
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((format!("{} checked in", guest.name), guest))
+}
+
+// Get checked-in versus expected counts, overall and per table
+#[ic_cdk::query]
+fn get_check_in_summary(wedding_id: u64) -> Result<CheckInSummary, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !can_check_in_guests(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple, planner or venue staff can view check-ins".to_string(),
+                ));
+            }
+
+            let mut summary = CheckInSummary::default();
+            for guest in wedding
+                .guest_list
+                .iter()
+                .filter(|guest| guest.rsvp_status == "confirmed")
+            {
+                // A guest's plus one arrives with them
+                let people = guest_headcount(guest);
+                let arrived = if guest.checked_in_at.is_some() {
+                    people
+                } else {
+                    0
+                };
+
+                let table = match summary
+                    .tables
+                    .iter_mut()
+                    .find(|table| table.table_assignment == guest.table_assignment)
+                {
+                    Some(table) => table,
+                    None => {
+                        summary.tables.push(TableCheckIn {
+                            table_assignment: guest.table_assignment.clone(),
+                            ..Default::default()
+                        });
+                        summary.tables.last_mut().unwrap()
+                    }
+                };
+                table.expected += people;
+                table.checked_in += arrived;
+                summary.expected += people;
+                summary.checked_in += arrived;
+            }
+
+            Ok(summary)
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Timeline Item Management
+ */
+
+// Add Timeline Item
+#[ic_cdk::update]
+fn add_timeline_item(
+    payload: TimelineItemPayload,
+) -> Result<(String, TimelineItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can edit the timeline".to_string(),
+        ));
+    }
+
+    if parse_timeline_time(&wedding, &payload.time).is_none() {
+        return Err(Message::InvalidDate(
+            "Time must be in HH:MM or YYYY-MM-DD HH:MM format".to_string(),
+        ));
+    }
+
+    if payload.duration_minutes > MAX_TIMELINE_ITEM_MINUTES {
+        return Err(Message::InvalidInput(format!(
+            "A timeline item can last at most {} minutes",
+            MAX_TIMELINE_ITEM_MINUTES
+        )));
+    }
+
+    // Create the new timeline item
+    let timeline_item = TimelineItem {
+        id: generate_uuid(),
+        wedding_id: payload.wedding_id,
+        time: payload.time.trim().to_string(),
+        duration_minutes: payload.duration_minutes,
+        description: payload.description.clone(),
+        responsible: payload.responsible.clone(),
+        status: payload.status.clone(),
+        started_at: None,
+        finished_at: None,
+        delay_minutes: 0,
+    };
+
+    // Update the wedding's timeline
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.timeline.push(timeline_item.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        timeline_change_message(&updated_wedding, &timeline_item, "added"),
+        timeline_item,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Update Timeline Item
+#[ic_cdk::update]
+fn update_timeline_item(
+    payload: UpdateTimelineItemPayload,
+) -> Result<(String, TimelineItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can edit the timeline".to_string(),
+        ));
+    }
+
+    if parse_timeline_time(&wedding, &payload.time).is_none() {
+        return Err(Message::InvalidDate(
+            "Time must be in HH:MM or YYYY-MM-DD HH:MM format".to_string(),
+        ));
+    }
+
+    if payload.duration_minutes > MAX_TIMELINE_ITEM_MINUTES {
+        return Err(Message::InvalidInput(format!(
+            "A timeline item can last at most {} minutes",
+            MAX_TIMELINE_ITEM_MINUTES
+        )));
+    }
+
+    // Fetch the timeline item from the wedding
+    let mut updated_wedding = wedding.clone();
+    let timeline_item = match updated_wedding
+        .timeline
+        .iter_mut()
+        .find(|item| item.id == payload.item_id)
+    {
+        Some(item) => item,
+        None => {
+            return Err(Message::Error("Timeline item not found".to_string()));
+        }
+    };
+
+    timeline_item.time = payload.time.trim().to_string();
+    timeline_item.duration_minutes = payload.duration_minutes;
+    timeline_item.description = payload.description.clone();
+    timeline_item.responsible = payload.responsible.clone();
+    timeline_item.status = payload.status.clone();
+    let timeline_item = timeline_item.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        timeline_change_message(&updated_wedding, &timeline_item, "updated"),
+        timeline_item,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Delete Timeline Item
+#[ic_cdk::update]
+fn delete_timeline_item(
+    wedding_id: u64,
+    item_id: u64,
+) -> Result<(String, TimelineItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can edit the timeline".to_string(),
+        ));
+    }
+
+    // Remove the item from the wedding's timeline
+    let mut updated_wedding = wedding.clone();
+    let timeline_item = match updated_wedding
+        .timeline
+        .iter()
+        .position(|item| item.id == item_id)
+    {
+        Some(index) => updated_wedding.timeline.remove(index),
+        None => {
+            return Err(Message::Error("Timeline item not found".to_string()));
+        }
+    };
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Timeline item deleted successfully".to_string(),
+        timeline_item,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Reorder the timeline; the order decides between items at the same time
+#[ic_cdk::update]
+fn reorder_timeline(
+    wedding_id: u64,
+    item_ids: Vec<u64>,
+) -> Result<(Vec<TimelineItem>, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can edit the timeline".to_string(),
+        ));
+    }
+
+    // Every item must be listed exactly once
+    let mut listed = item_ids.clone();
+    listed.sort_unstable();
+    listed.dedup();
+    let complete = listed.len() == item_ids.len()
+        && item_ids.len() == wedding.timeline.len()
+        && wedding
+            .timeline
+            .iter()
+            .all(|item| item_ids.contains(&item.id));
+    if !complete {
+        return Err(Message::InvalidInput(
+            "List every timeline item exactly once".to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.timeline = item_ids
+        .iter()
+        .filter_map(|item_id| wedding.timeline.iter().find(|item| item.id == *item_id))
+        .cloned()
+        .collect();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        sorted_timeline(&updated_wedding),
+        Message::Success("Timeline reordered successfully".to_string()),
+    ))
+}
+
+// Get the timeline items that overlap for the same responsible party
+#[ic_cdk::query]
+fn get_timeline_conflicts(wedding_id: u64) -> Result<Vec<TimelineConflict>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            let timeline = sorted_timeline(&wedding);
+            let mut conflicts = Vec::new();
+            for (index, item) in timeline.iter().enumerate() {
+                for other in timeline[index + 1..].iter() {
+                    if timeline_items_overlap(&wedding, item, other) {
+                        conflicts.push(TimelineConflict {
+                            item_id: item.id,
+                            other_item_id: other.id,
+                            responsible: item.responsible.trim().to_string(),
+                            message: format!(
+                                "\"{}\" at {} overlaps with \"{}\" at {}",
+                                item.description, item.time, other.description, other.time
+                            ),
+                        });
+                    }
+                }
+            }
+            Ok(conflicts)
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Live Day-of Tracking
+ */
+
+// Mark a timeline item as started
+#[ic_cdk::update]
+fn start_timeline_item(
+    wedding_id: u64,
+    item_id: u64,
+) -> Result<(String, TimelineItem, Wedding), Message> {
+    update_timeline_progress(wedding_id, item_id, true)
+}
+
+// Mark a timeline item as finished
+#[ic_cdk::update]
+fn finish_timeline_item(
+    wedding_id: u64,
+    item_id: u64,
+) -> Result<(String, TimelineItem, Wedding), Message> {
+    update_timeline_progress(wedding_id, item_id, false)
+}
+
+// Record a timeline item starting or finishing
+fn update_timeline_progress(
+    wedding_id: u64,
+    item_id: u64,
+    starting: bool,
+) -> Result<(String, TimelineItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    // Fetch the timeline item from the wedding
+    let item_index = match wedding.timeline.iter().position(|item| item.id == item_id) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Timeline item not found".to_string()));
+        }
+    };
+
+    if !can_update_timeline_item(&wedding, &wedding.timeline[item_index]) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple, their planner or the responsible vendor can update this item"
+                .to_string(),
+        ));
+    }
+
+    let mut updated_wedding = wedding.clone();
+    let timeline_item = &mut updated_wedding.timeline[item_index];
+    if timeline_item.finished_at.is_some() {
+        return Err(Message::Error(
+            "This timeline item has already finished".to_string(),
+        ));
+    }
+
+    let message = if starting {
+        if timeline_item.started_at.is_some() {
+            return Err(Message::Error(
+                "This timeline item has already started".to_string(),
+            ));
+        }
+        timeline_item.started_at = Some(current_timestamp());
+        timeline_item.status = "in-progress".to_string();
+        "Timeline item started"
+    } else {
+        // Finishing an item that was never marked started starts it too
+        if timeline_item.started_at.is_none() {
+            timeline_item.started_at = Some(current_timestamp());
+        }
+        timeline_item.finished_at = Some(current_timestamp());
+        timeline_item.status = "completed".to_string();
+        "Timeline item finished"
+    };
+    let timeline_item = timeline_item.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        message.to_string(),
+        timeline_item,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Delay a timeline item by some minutes, moving every later item that hasn't started
+#[ic_cdk::update]
+fn delay_timeline_item(
+    wedding_id: u64,
+    item_id: u64,
+    minutes: u64,
+) -> Result<(Vec<TimelineShift>, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
+
+    // Fetch the timeline item from the wedding
+    let timeline_item = match wedding.timeline.iter().find(|item| item.id == item_id) {
+        Some(item) => item.clone(),
+        None => {
+            return Err(Message::Error("Timeline item not found".to_string()));
+        }
+    };
+
+    if !can_update_timeline_item(&wedding, &timeline_item) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple, their planner or the responsible vendor can delay this item"
+                .to_string(),
+        ));
+    }
+
+    if minutes == 0 || minutes > MAX_TIMELINE_DELAY_MINUTES {
+        return Err(Message::InvalidInput(format!(
+            "Delay must be between 1 and {} minutes",
+            MAX_TIMELINE_DELAY_MINUTES
+        )));
+    }
+    if timeline_item.finished_at.is_some() {
+        return Err(Message::Error(
+            "This timeline item has already finished".to_string(),
+        ));
+    }
+    if parse_timeline_time(&wedding, &timeline_item.time).is_none() {
+        return Err(Message::InvalidDate(
+            "This timeline item has no valid time to move".to_string(),
+        ));
+    }
+
+    // A vendor's delay only moves their own items; the couple or a planner moves everyone's
+    let move_others = is_wedding_owner(&wedding) || is_wedding_planner(&wedding);
+    let mut updated_wedding = wedding.clone();
+    let shifts = delay_timeline(
+        &mut updated_wedding,
+        item_id,
+        minutes,
+        move_others,
+        &current_timestamp(),
+    );
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
+    });
+
+    Ok((
+        shifts,
+        Message::Success(format!("Timeline delayed by {} minutes", minutes)),
+    ))
+}
+
+// Get what is happening now, what is next and how far behind schedule the day is
+#[ic_cdk::query]
+fn get_live_timeline(wedding_id: u64) -> Result<LiveTimelineStatus, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            let is_booked_vendor = booked_vendors(&wedding)
+                .iter()
+                .any(|vendor| vendor.owner == caller());
+            if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) && !is_booked_vendor {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple, their planner or a booked vendor can follow the day"
+                        .to_string(),
+                ));
+            }
+            Ok(build_live_status(&wedding))
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get notifications about moved timeline items for the caller's booked vendors
+#[ic_cdk::query]
+fn get_vendor_notifications(wedding_id: u64) -> Result<Vec<VendorNotification>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if is_wedding_owner(&wedding) || is_wedding_planner(&wedding) {
+                return Ok(wedding.vendor_notifications.clone());
+            }
+            let vendor_ids: Vec<u64> = booked_vendors(&wedding)
+                .iter()
+                .filter(|vendor| vendor.owner == caller())
+                .map(|vendor| vendor.id)
+                .collect();
+            Ok(wedding
+                .vendor_notifications
+                .iter()
+                .filter(|notification| vendor_ids.contains(&notification.vendor_id))
+                .cloned()
+                .collect())
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/*
+ * Day-of Run Sheets
+ */
+
+// Get run sheets grouped by responsible party; a booked vendor gets their own
+#[ic_cdk::query]
+fn get_run_sheets(wedding_id: u64, responsible: Option<String>) -> Result<Vec<RunSheet>, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => visible_run_sheets(&wedding, responsible),
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get run sheets rendered as plain text or JSON, for printing
+#[ic_cdk::query]
+fn print_run_sheets(
+    wedding_id: u64,
+    responsible: Option<String>,
+    format: RunSheetFormat,
+) -> Result<String, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            let sheets = visible_run_sheets(&wedding, responsible)?;
+            match format {
+                RunSheetFormat::Json => serde_json::to_string(&sheets).map_err(|error| {
+                    Message::Error(format!("Could not render run sheets: {}", error))
+                }),
+                RunSheetFormat::Text => Ok(sheets
+                    .iter()
+                    .map(render_run_sheet)
+                    .collect::<Vec<String>>()
+                    .join("\n")),
+            }
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+/**
+ * Task Management
+ */
+
+// Add Task
+#[ic_cdk::update]
+fn add_task(payload: TaskPayload) -> Result<(String, Task, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can add tasks".to_string(),
+        ));
+    }
+
+    if payload.duration_days > MAX_TASK_DURATION_DAYS {
+        return Err(Message::InvalidInput(format!(
+            "A task can take at most {} days",
+            MAX_TASK_DURATION_DAYS
+        )));
+    }
+
+    // Generate a unique ID for the task
+    let task_id = generate_uuid();
+    validate_task_dependencies(&wedding.tasks, task_id, &payload.depends_on)?;
+
+    // Create the new task
+    let task = Task {
+        id: task_id,
+        title: payload.title.clone(),
+        description: payload.description.clone(),
+        deadline: payload.deadline.clone(),
+        assigned_to: payload.assigned_to.clone(),
+        status: "pending".to_string(),
+        budget: payload.budget,
+        duration_days: payload.duration_days,
+        depends_on: payload.depends_on.clone(),
+        checklist: None,
+    };
+
+    // Update the wedding's tasks
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.tasks.push(task.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Task added successfully".to_string(),
+        task,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Update Task Status
+#[ic_cdk::update]
+fn update_task_status(
+    payload: UpdateTaskStatusPayload,
+) -> Result<(String, Task, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can update task status".to_string(),
+        ));
+    }
+
+    // Fetch the task from the wedding
+    let task = wedding.tasks.iter().find(|task| task.id == payload.task_id);
+
+    let task = match task {
+        Some(task) => task.clone(),
+        None => {
+            return Err(Message::Error("Task not found".to_string()));
+        }
+    };
+
+    // A task can only be completed once everything it depends on is
+    if payload.status == "completed" {
+        let open: Vec<&str> = wedding
+            .tasks
+            .iter()
+            .filter(|other| task.depends_on.contains(&other.id) && other.status != "completed")
+            .map(|other| other.title.as_str())
+            .collect();
+        if !open.is_empty() {
+            return Err(Message::Error(format!(
+                "Complete these tasks first: {}",
+                open.join(", ")
+            )));
+        }
+    }
+
+    // Update the task status
+    let mut updated_task = task.clone();
+    updated_task.status = payload.status.clone();
+
+    // Update the wedding's tasks
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.tasks = updated_wedding
+        .tasks
+        .iter()
+        .map(|task| {
+            if task.id == payload.task_id {
+                updated_task.clone()
+            } else {
+                task.clone()
+            }
+        })
+        .collect();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Task status updated successfully".to_string(),
+        updated_task,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Delete Task
+#[ic_cdk::update]
+fn delete_task(payload: DeleteTaskPayload) -> Result<(String, Task, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
+        }
+    };
+
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can delete tasks".to_string(),
+        ));
+    }
+
+    // Fetch the task from the wedding
+    let task = wedding.tasks.iter().find(|task| task.id == payload.task_id);
+
+    let task = match task {
+        Some(task) => task.clone(),
+        None => {
+            return Err(Message::Error("Task not found".to_string()));
+        }
+    };
+
+    // Remove the task from the wedding's tasks
+    let mut updated_wedding = wedding.clone();
+    let updated_tasks: Vec<Task> = updated_wedding
+        .tasks
+        .iter()
+        .filter(|task| task.id != payload.task_id)
+        .cloned()
+        .collect();
+
+    updated_wedding.tasks = updated_tasks;
+
+    // Tasks that waited on the deleted task no longer do
+    for other in updated_wedding.tasks.iter_mut() {
+        other
+            .depends_on
+            .retain(|dependency| *dependency != payload.task_id);
+    }
 
-// Generate UUID
-fn generate_uuid() -> u64 {
-    let id = ID_COUNTER.with(|counter| {
-        let current_id = *counter.borrow().get(); // Use the binding to get the current ID
-        let _ = counter.borrow_mut().set(current_id + 1); // Now you can safely mutate the counter
-        current_id // Return the current_id
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
     });
 
-    id
+    // Return success
+    Ok((
+        "Task deleted successfully".to_string(),
+        task,
+        wedding_view(updated_wedding),
+    ))
 }
 
-/**
- * Canister Definition - Implementation of core functions
- */
+// Apply a checklist template to a wedding, or re-apply it after the date changed
+#[ic_cdk::update]
+fn apply_checklist_template(
+    wedding_id: u64,
+    template_id: u64,
+) -> Result<(Vec<Task>, Message), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
 
-/**
- * Vendor Management
- */
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                wedding_id
+            )))
+        }
+    };
 
-#[ic_cdk::update]
-fn register_vendor(payload: RegisterVendorPayload) -> Result<(Vendor, Message), Message> {
-    // Validate the user input to ensure all required fields are provided
-    if payload.name.is_empty() && payload.description.is_empty() && payload.service_cost == 0 {
-        return Err(Message::InvalidInput(
-            "Ensure all required fileds are provided.".to_string(),
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can apply checklist templates".to_string(),
         ));
     }
 
-    // Generate a unique ID for the vendor
-    let vendor_id = generate_uuid();
-
-    let vendor = Vendor {
-        id: vendor_id,
-        owner: caller(),
-        name: payload.name,
-        category: payload.category,
-        description: payload.description,
-        service_cost: payload.service_cost,
-        availability: payload.availability,
-        rating: 0,
-        reviews: Vec::new(),
-        bookings: Vec::new(),
-        verified: false,
-        portfolio: payload.portfolio,
+    let template = match find_checklist_template(Some(template_id)) {
+        Some(template) => template,
+        None => {
+            return Err(Message::Error("Checklist template not found".to_string()));
+        }
     };
 
-    // Store vendor in VENDOR_STORAGE
-    VENDOR_STORAGE.with(|vendors| {
-        vendors.borrow_mut().insert(vendor_id, vendor.clone());
+    let mut updated_wedding = wedding.clone();
+    let tasks = apply_checklist(&mut updated_wedding, &template)?;
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(wedding_id, updated_wedding.clone());
     });
 
     Ok((
-        vendor,
-        Message::Success("Vendor registered successfully".to_string()),
+        tasks,
+        Message::Success(format!(
+            "Checklist \"{}\" applied successfully",
+            template.name
+        )),
     ))
 }
 
-// Book Vendor for Wedding
+// Set the tasks a task depends on
 #[ic_cdk::update]
-fn book_vendor(
-    payload: VendorBookingPayload,
-) -> Result<(String, Wedding, Vendor, VendorBooking), Message> {
-    // Fetch the wedding and vendor from storage
+fn set_task_dependencies(
+    payload: SetTaskDependenciesPayload,
+) -> Result<(String, Task, Wedding), Message> {
+    // Fetch the wedding from storage
     let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
-    let vendor = VENDOR_STORAGE.with(|storage| storage.borrow().get(&payload.vendor_id));
 
     // Validate wedding existence
     let wedding = match wedding {
@@ -414,92 +9308,231 @@ fn book_vendor(
         }
     };
 
-    // Validate vendor existence
-    let vendor = match vendor {
-        Some(vendor) => vendor.clone(),
+    if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple or their planner can change task dependencies".to_string(),
+        ));
+    }
+
+    // Fetch the task from the wedding
+    let task_index = match wedding
+        .tasks
+        .iter()
+        .position(|task| task.id == payload.task_id)
+    {
+        Some(index) => index,
         None => {
-            return Err(Message::VendorNotFound(format!(
-                "Vendor with ID {} not found",
-                payload.vendor_id
-            )))
+            return Err(Message::Error("Task not found".to_string()));
         }
     };
 
-    // Check vendor availability
-    if !vendor.availability.iter().any(|date| date == &wedding.date) {
-        return Err(Message::DateUnavailable(format!(
-            "Vendor not available on wedding date {}",
-            wedding.date
-        )));
-    }
-
-    // Create the vendor booking
-    let vendor_booking = VendorBooking {
-        vendor_id: payload.vendor_id,
-        wedding_id: payload.wedding_id,
-        wedding_offer: payload.wedding_offer,
-        additional_details: payload.additional_details.clone(),
-        status: "pending".to_string(),
-        date: wedding.date.clone(),
-    };
+    let mut depends_on = payload.depends_on.clone();
+    depends_on.sort_unstable();
+    depends_on.dedup();
+    validate_task_dependencies(&wedding.tasks, payload.task_id, &depends_on)?;
 
-    // Update the wedding with the new vendor booking
     let mut updated_wedding = wedding.clone();
-    updated_wedding.vendors.push(vendor_booking.clone());
+    updated_wedding.tasks[task_index].depends_on = depends_on;
+    let updated_task = updated_wedding.tasks[task_index].clone();
 
+    // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
         storage
             .borrow_mut()
             .insert(payload.wedding_id, updated_wedding.clone());
     });
 
-    // Update the vendor with the new booking
-    let mut updated_vendor = vendor.clone();
-    updated_vendor.bookings.push(wedding.id.to_string());
-
-    VENDOR_STORAGE.with(|storage| {
-        storage
-            .borrow_mut()
-            .insert(payload.vendor_id, updated_vendor.clone());
-    });
-
     // Return success
     Ok((
-        "Vendor booked successfully".to_string(),
-        updated_wedding,
-        vendor,
-        vendor_booking,
+        "Task dependencies updated successfully".to_string(),
+        updated_task,
+        wedding_view(updated_wedding),
     ))
 }
 
-// Verify Vendor Booking done by the Vendor
+// Get the critical path to the wedding date and tasks that can't meet their deadlines
+#[ic_cdk::query]
+fn get_critical_path(wedding_id: u64) -> Result<CriticalPathReport, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => Ok(build_critical_path(&wedding)),
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get overdue tasks and timeline items, and tasks due soon (the couple or their planner)
+#[ic_cdk::query]
+fn get_attention_needed(wedding_id: u64) -> Result<AttentionReport, Message> {
+    WEDDING_STORAGE.with(|storage| match storage.borrow().get(&wedding_id) {
+        Some(wedding) => {
+            if !is_wedding_owner(&wedding) && !is_wedding_planner(&wedding) {
+                return Err(Message::UnauthorizedAction(
+                    "Only the couple or their planner can see what needs attention".to_string(),
+                ));
+            }
+
+            let now = current_datetime();
+            let due_soon_until = now.date() + chrono::Duration::days(TASK_DUE_SOON_DAYS);
+            Ok(AttentionReport {
+                overdue_tasks: wedding
+                    .tasks
+                    .iter()
+                    .filter(|task| task_is_overdue(task, now.date()))
+                    .cloned()
+                    .collect(),
+                overdue_timeline_items: sorted_timeline(&wedding)
+                    .into_iter()
+                    .filter(|item| timeline_item_is_overdue(&wedding, item, now))
+                    .collect(),
+                tasks_due_soon: wedding
+                    .tasks
+                    .iter()
+                    .filter(|task| task.status == "pending" || task.status == "in-progress")
+                    .filter(|task| {
+                        parse_date(&task.deadline).is_some_and(|deadline| {
+                            deadline >= now.date() && deadline <= due_soon_until
+                        })
+                    })
+                    .cloned()
+                    .collect(),
+            })
+        }
+        None => Err(Message::WeddingNotFound(
+            "Wedding with the provided ID not found".to_string(),
+        )),
+    })
+}
+
+// Get Task List
+#[ic_cdk::query]
+fn get_task_list(wedding_id: u64) -> Result<Vec<Task>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if wedding.tasks.is_empty() {
+                    Err(Message::Error(
+                        "No tasks found for this wedding".to_string(),
+                    ))
+                } else {
+                    Ok(wedding.tasks.clone())
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Get Task Details
+#[ic_cdk::query]
+fn get_task_details(wedding_id: u64, task_id: u64) -> Result<Task, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                let task = wedding.tasks.iter().find(|task| task.id == task_id);
+
+                match task {
+                    Some(task) => Ok(task.clone()),
+                    None => Err(Message::Error("Task not found".to_string())),
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+/*
+ * Registry Management
+ */
+
+// Add Registry Item
 #[ic_cdk::update]
-fn verify_vendor_booking(
-    vendor_id: u64,
-    wedding_id: u64,
-) -> Result<(VendorBooking, Message), Message> {
-    // Fetch the vendor from storage
-    let vendor = VENDOR_STORAGE.with(|storage| storage.borrow().get(&vendor_id));
+fn add_registry_item(
+    payload: AddRegistryItemPayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
-    // Validate vendor existence
-    let vendor = match vendor {
-        Some(vendor) => vendor.clone(),
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
         None => {
-            return Err(Message::VendorNotFound(format!(
-                "Vendor with ID {} not found",
-                vendor_id
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
             )))
         }
     };
 
-    if vendor.owner != caller() {
+    if !is_wedding_owner(&wedding) {
         return Err(Message::UnauthorizedAction(
-            "You are not authorized to perform this action".to_string(),
+            "Only the couple can add registry items".to_string(),
+        ));
+    }
+
+    if payload.name.is_empty() || payload.quantity_desired == 0 {
+        return Err(Message::InvalidInput(
+            "Registry item name and a desired quantity of at least 1 are required".to_string(),
         ));
     }
 
+    // Create the new registry item
+    let registry_item = RegistryItem {
+        id: generate_uuid(),
+        kind: RegistryItemKind::Item,
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        price: payload.price,
+        quantity_desired: payload.quantity_desired,
+        quantity_received: 0,
+        external_url: payload.external_url.clone(),
+        sku: payload.sku.clone(),
+        priority: payload.priority.clone(),
+        category: payload.category.clone(),
+        status: "available".to_string(),
+        purchased_by: "".to_string(),
+        purchases: Vec::new(),
+        amount_funded: 0,
+        contributions: Vec::new(),
+        reserved_by: None,
+        reserved_until: None,
+        experiences: Vec::new(),
+    };
+
+    // Update the wedding's registry
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.registry.push(registry_item.clone());
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
+
+    // Return success
+    Ok((
+        "Registry item added successfully".to_string(),
+        registry_item,
+        wedding_view(updated_wedding),
+    ))
+}
+
+// Update Registry Item details by ID
+#[ic_cdk::update]
+fn update_registry_item(
+    payload: UpdateRegistryItemPayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
     // Fetch the wedding from storage
-    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
     // Validate wedding existence
     let wedding = match wedding {
@@ -507,266 +9540,179 @@ fn verify_vendor_booking(
         None => {
             return Err(Message::WeddingNotFound(format!(
                 "Wedding with ID {} not found",
-                wedding_id
+                payload.wedding_id
             )))
         }
     };
 
-    // Fetch the vendor booking from the wedding
-    let booking = wedding
-        .vendors
-        .iter()
-        .find(|booking| booking.vendor_id == vendor_id);
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can edit registry items".to_string(),
+        ));
+    }
 
-    let booking = match booking {
-        Some(booking) => booking.clone(),
+    // Fetch the registry item from the wedding
+    let item_index = match find_registry_item(&wedding, Some(payload.item_id), "") {
+        Some(index) => index,
         None => {
-            return Err(Message::Error("Vendor booking not found".to_string()));
+            return Err(Message::Error("Registry item not found".to_string()));
         }
     };
+    let item = wedding.registry[item_index].clone();
 
-    // Update the vendor booking status
-    let mut updated_booking = booking.clone();
-    updated_booking.status = "confirmed".to_string();
+    if payload.name.is_empty() || payload.quantity_desired == 0 {
+        return Err(Message::InvalidInput(
+            "Registry item name and a desired quantity of at least 1 are required".to_string(),
+        ));
+    }
 
-    // Save the updated vendor booking details
-    WEDDING_STORAGE.with(|storage| {
-        let mut updated_wedding = wedding.clone();
-        updated_wedding.vendors = updated_wedding
-            .vendors
-            .iter()
-            .map(|booking| {
-                if booking.vendor_id == vendor_id {
-                    updated_booking.clone()
-                } else {
-                    booking.clone()
-                }
-            })
-            .collect();
+    // The item can't be shrunk below what guests have already given; funds are open-ended
+    if item.kind == RegistryItemKind::Item
+        && (payload.quantity_desired < item.quantity_received
+            || payload
+                .price
+                .saturating_mul(payload.quantity_desired - item.quantity_received)
+                < item.amount_funded)
+    {
+        return Err(Message::InvalidInput(
+            "Price and quantity can't drop below what has already been received".to_string(),
+        ));
+    }
+
+    // Update the registry item details
+    let mut updated_item = item.clone();
+    updated_item.name = payload.name.clone();
+    updated_item.description = payload.description.clone();
+    updated_item.price = payload.price;
+    updated_item.quantity_desired = payload.quantity_desired;
+    updated_item.external_url = payload.external_url.clone();
+    updated_item.sku = payload.sku.clone();
+    updated_item.priority = payload.priority.clone();
+    updated_item.category = payload.category.clone();
+
+    // Re-open the item if the couple asked for more units
+    if (item.status == "purchased" || item.status == "fulfilled")
+        && registry_item_remaining(&updated_item) > 0
+    {
+        reopen_registry_item(&mut updated_item);
+    }
 
+    // Update the wedding's registry
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.registry[item_index] = updated_item.clone();
+
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
         storage
             .borrow_mut()
-            .insert(wedding_id, updated_wedding.clone());
+            .insert(payload.wedding_id, updated_wedding.clone());
     });
 
     // Return success
     Ok((
-        updated_booking,
-        Message::Success("Vendor booking verified successfully".to_string()),
+        "Registry item updated successfully".to_string(),
+        updated_item,
+        wedding_view(updated_wedding),
     ))
 }
 
-// Verify Vendor
+// Add Registry Fund (honeymoon, house deposit, ...)
 #[ic_cdk::update]
-fn verify_vendor(vendor_id: u64) -> Result<(Vendor, Message), Message> {
-    // Fetch the vendor from storage
-    let vendor = VENDOR_STORAGE.with(|storage| storage.borrow().get(&vendor_id));
+fn add_registry_fund(
+    payload: AddRegistryFundPayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
-    // Validate vendor existence
-    let vendor = match vendor {
-        Some(vendor) => vendor.clone(),
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
         None => {
-            return Err(Message::VendorNotFound(format!(
-                "Vendor with ID {} not found",
-                vendor_id
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
             )))
         }
     };
 
-    // Update the vendor's verified status
-    let mut updated_vendor = vendor.clone();
-    updated_vendor.verified = true;
-
-    // Save the updated vendor details
-    VENDOR_STORAGE.with(|storage| {
-        storage
-            .borrow_mut()
-            .insert(vendor_id, updated_vendor.clone());
-    });
-
-    // Return success
-    Ok((
-        updated_vendor,
-        Message::Success("Vendor verified successfully".to_string()),
-    ))
-}
-
-/**
- * Vendor Queries
- */
-
-// Get Vendor Details by ID
-#[ic_cdk::query]
-fn get_vendor_details(vendor_id: u64) -> Result<Vendor, Message> {
-    VENDOR_STORAGE.with(|vendors| match vendors.borrow().get(&vendor_id) {
-        Some(vendor) => Ok(vendor.clone()),
-        None => Err(Message::VendorNotFound(format!(
-            "Vendor with id={} not found",
-            vendor_id
-        ))),
-    })
-}
-
-// Get Vendors by Category
-#[ic_cdk::query]
-fn search_vendors_by_category(category: Category) -> Result<Vec<Vendor>, Message> {
-    let matching_vendors: Vec<Vendor> = VENDOR_STORAGE.with(|vendors| {
-        vendors
-            .borrow()
-            .iter()
-            .filter_map(|(_, vendor)| {
-                if vendor.category == category {
-                    Some(vendor.clone()) // Include matching vendor
-                } else {
-                    None // Skip non-matching vendor
-                }
-            })
-            .collect() // Collect all matching vendors into a Vec
-    });
-
-    if matching_vendors.is_empty() {
-        Err(Message::VendorNotFound(format!(
-            "No vendors found in the '{}' category",
-            format!("{:?}", category) // Convert the enum to a readable string
-        )))
-    } else {
-        Ok(matching_vendors)
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can add funds to the registry".to_string(),
+        ));
     }
-}
-
-// Get All Vendors
-#[ic_cdk::query]
-fn get_all_vendors() -> Result<Vec<Vendor>, Message> {
-    VENDOR_STORAGE.with(|vendors| {
-        let all_vendors: Vec<Vendor> = vendors
-            .borrow()
-            .iter()
-            .map(|(_, vendor)| vendor.clone()) // Clone each vendor to return owned data
-            .collect();
 
-        if all_vendors.is_empty() {
-            Err(Message::VendorNotFound("No vendors found.".to_string()))
-        } else {
-            Ok(all_vendors)
-        }
-    })
-}
+    if payload.name.is_empty() || payload.target_amount == 0 {
+        return Err(Message::InvalidInput(
+            "Fund name and a non-zero target amount are required".to_string(),
+        ));
+    }
 
-/**
- * Wedding Management
- */
-#[ic_cdk::update]
-fn create_wedding(payload: CreateWeddingPayload) -> Result<(Wedding, Message), Message> {
-    // Validate the user input to ensure all required fields are provided
-    if payload.couple_names.is_empty()
-        && payload.date.is_empty()
-        && payload.budget == 0
-        && payload.location.is_empty()
-        && payload.guest_count == 0
+    if payload
+        .experiences
+        .iter()
+        .any(|experience| experience.name.is_empty() || experience.amount == 0)
     {
         return Err(Message::InvalidInput(
-            "Ensure all required fileds are provided.".to_string(),
+            "Every experience needs a name and a non-zero amount".to_string(),
         ));
     }
 
-    // Generate a unique ID for the wedding
-    let wedding_id = generate_uuid();
-
-    let wedding = Wedding {
-        id: wedding_id,
-        couple_names: payload.couple_names,
-        date: payload.date,
-        budget: payload.budget,
-        location: payload.location,
-        guest_count: payload.guest_count,
-        vendors: Vec::new(),
-        timeline: Vec::new(),
-        tasks: Vec::new(),
-        guest_list: Vec::new(),
-        registry: Vec::new(),
-        status: "planning".to_string(),
+    // Create the new fund
+    let fund = RegistryItem {
+        id: generate_uuid(),
+        kind: RegistryItemKind::Fund,
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        price: payload.target_amount,
+        quantity_desired: 1,
+        quantity_received: 0,
+        external_url: None,
+        sku: None,
+        priority: payload.priority.clone(),
+        category: payload.category.clone(),
+        status: "available".to_string(),
+        purchased_by: "".to_string(),
+        purchases: Vec::new(),
+        amount_funded: 0,
+        contributions: Vec::new(),
+        reserved_by: None,
+        reserved_until: None,
+        experiences: payload
+            .experiences
+            .iter()
+            .map(|experience| FundExperience {
+                id: generate_uuid(),
+                name: experience.name.clone(),
+                amount: experience.amount,
+                amount_funded: 0,
+            })
+            .collect(),
     };
 
-    WEDDING_STORAGE.with(|weddings| {
-        weddings.borrow_mut().insert(wedding_id, wedding.clone());
-    });
-
-    Ok((
-        wedding,
-        Message::Success("Wedding created successfully".to_string()),
-    ))
-}
-
-/**
- * Wedding Queries
- */
+    // Update the wedding's registry
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.registry.push(fund.clone());
 
-#[ic_cdk::query]
-fn get_wedding_details(wedding_id: u64) -> Result<Wedding, Message> {
+    // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
         storage
-            .borrow()
-            .get(&wedding_id)
-            .ok_or(Message::WeddingNotFound("Wedding not found".to_string()))
-    })
-}
-
-// Get All Weddings
-#[ic_cdk::query]
-fn get_all_weddings() -> Result<Vec<Wedding>, Message> {
-    WEDDING_STORAGE.with(|weddings| {
-        let all_weddings: Vec<Wedding> = weddings
-            .borrow()
-            .iter()
-            .map(|(_, wedding)| wedding.clone()) // Clone each wedding to return owned data
-            .collect();
-
-        if all_weddings.is_empty() {
-            Err(Message::WeddingNotFound("No weddings found.".to_string()))
-        } else {
-            Ok(all_weddings)
-        }
-    })
-}
-
-// Get Wedding Timeline
-#[ic_cdk::query]
-fn get_wedding_timeline(wedding_id: u64) -> Result<Vec<TimelineItem>, Message> {
-    WEDDING_STORAGE.with(|storage| {
-        let wedding = storage.borrow().get(&wedding_id);
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
 
-        match wedding {
-            Some(wedding) => {
-                if wedding.timeline.is_empty() {
-                    Err(Message::NoTimeLineItemsFound(
-                        "No timeline items found for this wedding".to_string(),
-                    ))
-                } else {
-                    Ok(wedding.timeline.clone())
-                }
-            }
-            None => Err(Message::WeddingNotFound(
-                "Wedding with the provided ID not found".to_string(),
-            )),
-        }
-    })
+    // Return success
+    Ok((
+        "Registry fund added successfully".to_string(),
+        fund,
+        wedding_view(updated_wedding),
+    ))
 }
 
-/*
- * Guest Management
- * RSVP Submission
- * Guest RSVP Approval
- * Table Assignment
- * Guest Queries
- * Guest RSVP Queries
- * Guest RSVP List
- * Guest RSVP Status
- * Guest RSVP Count
- */
-
-// Guest RSVP Submission
+// Add Experience to a Registry Fund
 #[ic_cdk::update]
-fn guest_rsvp(payload: GuestRsvpPayload) -> Result<(String, Guest, Wedding), Message> {
+fn add_fund_experience(
+    payload: AddFundExperiencePayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
     // Fetch the wedding from storage
     let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
@@ -781,29 +9727,45 @@ fn guest_rsvp(payload: GuestRsvpPayload) -> Result<(String, Guest, Wedding), Mes
         }
     };
 
-    // Check if the guest already exists
-    let guest_exists = wedding
-        .guest_list
-        .iter()
-        .any(|guest| guest.guest_email == payload.guest_email);
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can edit registry funds".to_string(),
+        ));
+    }
 
-    if guest_exists {
-        return Err(Message::Error("Guest RSVP already submitted".to_string()));
+    if payload.name.is_empty() || payload.amount == 0 {
+        return Err(Message::InvalidInput(
+            "Experience name and a non-zero amount are required".to_string(),
+        ));
     }
 
-    // Create the new guest RSVP
-    let guest = Guest {
-        name: payload.name.clone(),
-        guest_email: payload.guest_email.clone(),
-        rsvp_status: "pending".to_string(),
-        dietary_restrictions: payload.dietary_restrictions.clone(),
-        plus_one: payload.plus_one,
-        table_assignment: TableAssignment::Unassigned,
+    // Fetch the fund from the wedding
+    let item_index = match find_registry_item(&wedding, Some(payload.item_id), "") {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Registry item not found".to_string()));
+        }
     };
+    let item = wedding.registry[item_index].clone();
 
-    // Update the wedding's guest list
+    if item.kind != RegistryItemKind::Fund {
+        return Err(Message::InvalidInput(
+            "Experiences can only be added to a fund".to_string(),
+        ));
+    }
+
+    // Add the experience to the fund
+    let mut updated_item = item.clone();
+    updated_item.experiences.push(FundExperience {
+        id: generate_uuid(),
+        name: payload.name.clone(),
+        amount: payload.amount,
+        amount_funded: 0,
+    });
+
+    // Update the wedding's registry
     let mut updated_wedding = wedding.clone();
-    updated_wedding.guest_list.push(guest.clone());
+    updated_wedding.registry[item_index] = updated_item.clone();
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
@@ -814,86 +9776,142 @@ fn guest_rsvp(payload: GuestRsvpPayload) -> Result<(String, Guest, Wedding), Mes
 
     // Return success
     Ok((
-        "Guest RSVP submitted successfully".to_string(),
-        guest,
-        updated_wedding,
+        "Fund experience added successfully".to_string(),
+        updated_item,
+        wedding_view(updated_wedding),
     ))
 }
 
-// Guest RSVP Approval and Table Assignment
+// Mark a Registry Contribution as thanked
 #[ic_cdk::update]
-fn approve_rsvp(payload: ApproveRsvpPayload) -> Result<(String, Guest, Wedding), Message> {
+fn mark_contribution_thanked(
+    wedding_id: u64,
+    contribution_id: u64,
+) -> Result<(RegistryContribution, Message), Message> {
     // Fetch the wedding from storage
-    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
 
     // Validate wedding existence
-    let wedding = match wedding {
+    let mut wedding = match wedding {
         Some(wedding) => wedding.clone(),
         None => {
             return Err(Message::WeddingNotFound(format!(
                 "Wedding with ID {} not found",
-                payload.wedding_id
+                wedding_id
             )))
         }
     };
 
-    // Confirm if there is still available seating capacity
-    let confirmed_guests = wedding
-        .guest_list
-        .iter()
-        .filter(|guest| guest.rsvp_status == "confirmed")
-        .fold(0, |count, guest| count + if guest.plus_one { 2 } else { 1 });
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can track thank-you notes".to_string(),
+        ));
+    }
 
-    if confirmed_guests >= wedding.guest_count as usize {
-        // Check if there's only one seat left, but the guest is bringing a plus one
-        if wedding.guest_count as usize - confirmed_guests == 1 {
-            return Err(Message::Error(
-                "Only one seat is available, but a plus one is requested".to_string(),
-            ));
+    // Find and update the contribution
+    let contribution = wedding
+        .registry
+        .iter_mut()
+        .flat_map(|item| item.contributions.iter_mut())
+        .find(|contribution| contribution.id == contribution_id);
+
+    let contribution = match contribution {
+        Some(contribution) => {
+            contribution.thanked = true;
+            contribution.clone()
+        }
+        None => {
+            return Err(Message::Error("Contribution not found".to_string()));
+        }
+    };
+
+    // Keep the gift ledger entry for this contribution in step
+    for gift in wedding.gifts.iter_mut() {
+        if gift.source == GiftSource::RegistryContribution
+            && gift.source_record_id == Some(contribution_id)
+            && gift.thank_you_status != ThankYouStatus::Sent
+        {
+            gift.thank_you_status = ThankYouStatus::Sent;
+            gift.thanked_date = Some(current_datetime().date().to_string());
         }
-        return Err(Message::BudgetExceeded(
-            "Guest count exceeds the available seats".to_string(),
-        ));
     }
 
-    // Check if the guest already exists
-    let guest = wedding
-        .guest_list
-        .iter()
-        .find(|guest| guest.guest_email == payload.guest_email);
+    // Save the updated wedding details
+    WEDDING_STORAGE.with(|storage| {
+        storage.borrow_mut().insert(wedding_id, wedding);
+    });
 
-    let guest = match guest {
-        Some(guest) => guest.clone(),
+    Ok((
+        contribution,
+        Message::Success("Contribution marked as thanked".to_string()),
+    ))
+}
+
+// Update Registry Item Status
+#[ic_cdk::update]
+fn update_registry_item_status(
+    payload: UpdateRegistryItemStatusPayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
         None => {
-            return Err(Message::Error("Guest not found".to_string()));
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
         }
     };
 
-    // Update the guest's RSVP status and table assignment
-    let mut updated_guest = guest.clone();
-    updated_guest.rsvp_status = "confirmed".to_string();
-    updated_guest.table_assignment = payload.table_assignment.clone();
-
-    // Update the wedding's guest list
-    let mut updated_wedding = wedding.clone();
-    updated_wedding.guest_list = updated_wedding
-        .guest_list
-        .iter()
-        .map(|guest| {
-            if guest.guest_email == payload.guest_email {
-                updated_guest.clone()
-            } else {
-                guest.clone()
-            }
-        })
-        .collect();
+    // Fetch the registry item from the wedding, by ID or by name
+    let item_index = match find_registry_item(&wedding, payload.item_id, &payload.item_name) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Registry item not found".to_string()));
+        }
+    };
+    let item = wedding.registry[item_index].clone();
+
+    // Guests buy through reserve_registry_item and confirm_registry_purchase; here they
+    // can only hand back a reservation they hold
+    let releasing_own_reservation = payload.status == "available"
+        && is_reservation_active(&item)
+        && item.reserved_by == Some(caller());
+    if !is_wedding_owner(&wedding) && !releasing_own_reservation {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can change a registry item's status directly".to_string(),
+        ));
+    }
 
-    // Update the available seats -1 if plus one is false
-    if !guest.plus_one {
-        updated_wedding.guest_count -= 1;
-    } else {
-        updated_wedding.guest_count -= 2;
+    // Update the registry item status
+    let mut updated_item = item.clone();
+    updated_item.status = payload.status.clone();
+    if is_wedding_owner(&wedding) {
+        updated_item.purchased_by = payload.purchased_by.clone();
     }
+    updated_item.reserved_by = None;
+    updated_item.reserved_until = None;
+
+    // Marking an item purchased through this endpoint covers every unit not yet bought or funded
+    let units_left = item
+        .quantity_desired
+        .saturating_sub(item.quantity_received + registry_units_funded(&item));
+    if payload.status == "purchased" && units_left > 0 {
+        updated_item.purchases.push(RegistryPurchase {
+            id: generate_uuid(),
+            purchased_by: payload.purchased_by.clone(),
+            quantity: units_left,
+            date: current_timestamp(),
+        });
+        updated_item.quantity_received += units_left;
+    }
+
+    // Update the wedding's registry
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.registry[item_index] = updated_item.clone();
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
@@ -904,113 +9922,75 @@ fn approve_rsvp(payload: ApproveRsvpPayload) -> Result<(String, Guest, Wedding),
 
     // Return success
     Ok((
-        "RSVP approved and table assigned successfully".to_string(),
-        updated_guest,
-        updated_wedding,
+        "Registry item status updated successfully".to_string(),
+        updated_item,
+        wedding_view(updated_wedding),
     ))
 }
 
-/*
- * Guest Queries
- */
-
-// Guest List
-#[ic_cdk::query]
-fn get_guest_list(wedding_id: u64) -> Result<Vec<Guest>, Message> {
-    WEDDING_STORAGE.with(|storage| {
-        let wedding = storage.borrow().get(&wedding_id);
+// Reserve Registry Item for a limited window
+#[ic_cdk::update]
+fn reserve_registry_item(
+    payload: ReserveRegistryItemPayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
+    // Fetch the wedding from storage
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
-        match wedding {
-            Some(wedding) => {
-                if wedding.guest_list.is_empty() {
-                    Err(Message::Error(
-                        "No guests found for this wedding".to_string(),
-                    ))
-                } else {
-                    Ok(wedding.guest_list.clone())
-                }
-            }
-            None => Err(Message::WeddingNotFound(
-                "Wedding with the provided ID not found".to_string(),
-            )),
+    // Validate wedding existence
+    let wedding = match wedding {
+        Some(wedding) => wedding.clone(),
+        None => {
+            return Err(Message::WeddingNotFound(format!(
+                "Wedding with ID {} not found",
+                payload.wedding_id
+            )))
         }
-    })
-}
-
-// Guest RSVP List
-#[ic_cdk::query]
-fn get_guest_details(wedding_id: u64, guest_email: String) -> Result<Guest, Message> {
-    WEDDING_STORAGE.with(|storage| {
-        let wedding = storage.borrow().get(&wedding_id);
-
-        match wedding {
-            Some(wedding) => {
-                let guest = wedding
-                    .guest_list
-                    .iter()
-                    .find(|guest| guest.guest_email == guest_email);
+    };
 
-                match guest {
-                    Some(guest) => Ok(guest.clone()),
-                    None => Err(Message::Error("Guest not found".to_string())),
-                }
-            }
-            None => Err(Message::WeddingNotFound(
-                "Wedding with the provided ID not found".to_string(),
-            )),
+    // Fetch the registry item from the wedding, by ID or by name
+    let item_index = match find_registry_item(&wedding, payload.item_id, &payload.item_name) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Registry item not found".to_string()));
         }
-    })
-}
-
-// Guest RSVP Status
-#[ic_cdk::query]
-fn get_guest_rsvp_status(wedding_id: u64, guest_email: String) -> Result<String, Message> {
-    WEDDING_STORAGE.with(|storage| {
-        let wedding = storage.borrow().get(&wedding_id);
+    };
+    let item = wedding.registry[item_index].clone();
 
-        match wedding {
-            Some(wedding) => {
-                let guest = wedding
-                    .guest_list
-                    .iter()
-                    .find(|guest| guest.guest_email == guest_email);
+    // Reserve the item for the caller
+    let mut updated_item = item.clone();
+    reserve_registry_item_for(&mut updated_item, caller(), ic_cdk::api::time())?;
 
-                match guest {
-                    Some(guest) => Ok(guest.rsvp_status.clone()),
-                    None => Err(Message::Error("Guest not found".to_string())),
-                }
-            }
-            None => Err(Message::WeddingNotFound(
-                "Wedding with the provided ID not found".to_string(),
-            )),
-        }
-    })
-}
+    // Update the wedding's registry
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.registry[item_index] = updated_item.clone();
 
-// Guest RSVP Count
-#[ic_cdk::query]
-fn get_guest_rsvp_count(wedding_id: u64) -> Result<u64, Message> {
+    // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
-        let wedding = storage.borrow().get(&wedding_id);
+        storage
+            .borrow_mut()
+            .insert(payload.wedding_id, updated_wedding.clone());
+    });
 
-        match wedding {
-            Some(wedding) => Ok(wedding.guest_list.len() as u64),
-            None => Err(Message::WeddingNotFound(
-                "Wedding with the provided ID not found".to_string(),
-            )),
-        }
-    })
-}
+    // Release the reservation automatically once the window lapses
+    let wedding_id = payload.wedding_id;
+    let item_id = updated_item.id;
+    ic_cdk_timers::set_timer(REGISTRY_RESERVATION_WINDOW, move || {
+        release_expired_reservation(wedding_id, item_id)
+    });
 
-/*
- * Timeline Item Management
- */
+    // Return success
+    Ok((
+        "Registry item reserved successfully".to_string(),
+        updated_item,
+        wedding_view(updated_wedding),
+    ))
+}
 
-// Add Timeline Item
+// Confirm Registry Purchase by the guest holding the reservation
 #[ic_cdk::update]
-fn add_timeline_item(
-    payload: TimelineItemPayload,
-) -> Result<(String, TimelineItem, Wedding), Message> {
+fn confirm_registry_purchase(
+    payload: ConfirmRegistryPurchasePayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
     // Fetch the wedding from storage
     let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
@@ -1025,18 +10005,27 @@ fn add_timeline_item(
         }
     };
 
-    // Create the new timeline item
-    let timeline_item = TimelineItem {
-        wedding_id: payload.wedding_id,
-        time: payload.time.clone(),
-        description: payload.description.clone(),
-        responsible: payload.responsible.clone(),
-        status: payload.status.clone(),
+    // Fetch the registry item from the wedding, by ID or by name
+    let item_index = match find_registry_item(&wedding, payload.item_id, &payload.item_name) {
+        Some(index) => index,
+        None => {
+            return Err(Message::Error("Registry item not found".to_string()));
+        }
     };
+    let item = wedding.registry[item_index].clone();
 
-    // Update the wedding's timeline
+    let mut updated_item = item.clone();
+    confirm_reserved_purchase(
+        &mut updated_item,
+        caller(),
+        &payload.purchased_by,
+        ic_cdk::api::time(),
+        &current_timestamp(),
+    )?;
+
+    // Update the wedding's registry
     let mut updated_wedding = wedding.clone();
-    updated_wedding.timeline.push(timeline_item.clone());
+    updated_wedding.registry[item_index] = updated_item.clone();
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
@@ -1047,21 +10036,20 @@ fn add_timeline_item(
 
     // Return success
     Ok((
-        "Timeline item added successfully".to_string(),
-        timeline_item,
-        updated_wedding,
+        "Registry purchase confirmed successfully".to_string(),
+        updated_item,
+        wedding_view(updated_wedding),
     ))
 }
 
-/**
- * Task Management
- */
-
-// Add Task
+// Hide or show who bought registry items until after the wedding
 #[ic_cdk::update]
-fn add_task(payload: TaskPayload) -> Result<(String, Task, Wedding), Message> {
+fn set_registry_privacy(
+    wedding_id: u64,
+    hide_purchasers: bool,
+) -> Result<(Wedding, Message), Message> {
     // Fetch the wedding from storage
-    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
 
     // Validate wedding existence
     let wedding = match wedding {
@@ -1069,45 +10057,45 @@ fn add_task(payload: TaskPayload) -> Result<(String, Task, Wedding), Message> {
         None => {
             return Err(Message::WeddingNotFound(format!(
                 "Wedding with ID {} not found",
-                payload.wedding_id
+                wedding_id
             )))
         }
     };
 
-    // Generate a unique ID for the task
-    let task_id = generate_uuid();
-
-    // Create the new task
-    let task = Task {
-        id: task_id,
-        title: payload.title.clone(),
-        description: payload.description.clone(),
-        deadline: payload.deadline.clone(),
-        assigned_to: payload.assigned_to.clone(),
-        status: "pending".to_string(),
-        budget: payload.budget,
-    };
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can change registry privacy".to_string(),
+        ));
+    }
 
-    // Update the wedding's tasks
     let mut updated_wedding = wedding.clone();
-    updated_wedding.tasks.push(task.clone());
+    updated_wedding.hide_registry_purchasers = hide_purchasers;
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
         storage
             .borrow_mut()
-            .insert(payload.wedding_id, updated_wedding.clone());
+            .insert(wedding_id, updated_wedding.clone());
     });
 
-    // Return success
-    Ok(("Task added successfully".to_string(), task, updated_wedding))
+    Ok((
+        wedding_view(updated_wedding),
+        Message::Success("Registry privacy updated successfully".to_string()),
+    ))
 }
 
-// Update Task Status
+// Contribute To Registry Item (group gifting)
 #[ic_cdk::update]
-fn update_task_status(
-    payload: UpdateTaskStatusPayload,
-) -> Result<(String, Task, Wedding), Message> {
+fn contribute_to_registry_item(
+    payload: ContributeToRegistryItemPayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
+    // Validate the user input
+    if payload.contributor.is_empty() || payload.amount == 0 {
+        return Err(Message::InvalidInput(
+            "Contributor name and a non-zero amount are required".to_string(),
+        ));
+    }
+
     // Fetch the wedding from storage
     let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
@@ -1122,33 +10110,37 @@ fn update_task_status(
         }
     };
 
-    // Fetch the task from the wedding
-    let task = wedding.tasks.iter().find(|task| task.id == payload.task_id);
-
-    let task = match task {
-        Some(task) => task.clone(),
+    // Fetch the registry item from the wedding, by ID or by name
+    let item_index = match find_registry_item(&wedding, payload.item_id, &payload.item_name) {
+        Some(index) => index,
         None => {
-            return Err(Message::Error("Task not found".to_string()));
+            return Err(Message::Error("Registry item not found".to_string()));
         }
     };
+    let item = wedding.registry[item_index].clone();
 
-    // Update the task status
-    let mut updated_task = task.clone();
-    updated_task.status = payload.status.clone();
+    let mut updated_item = item.clone();
+    apply_registry_contribution(
+        &mut updated_item,
+        payload.amount,
+        payload.experience_id,
+        ic_cdk::api::time(),
+    )?;
+
+    // Record the contribution
+    updated_item.contributions.push(RegistryContribution {
+        id: generate_uuid(),
+        contributor: payload.contributor.clone(),
+        amount: payload.amount,
+        message: payload.message.clone(),
+        date: current_timestamp(),
+        experience_id: payload.experience_id,
+        thanked: false,
+    });
 
-    // Update the wedding's tasks
+    // Update the wedding's registry
     let mut updated_wedding = wedding.clone();
-    updated_wedding.tasks = updated_wedding
-        .tasks
-        .iter()
-        .map(|task| {
-            if task.id == payload.task_id {
-                updated_task.clone()
-            } else {
-                task.clone()
-            }
-        })
-        .collect();
+    updated_wedding.registry[item_index] = updated_item.clone();
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
@@ -1159,15 +10151,17 @@ fn update_task_status(
 
     // Return success
     Ok((
-        "Task status updated successfully".to_string(),
-        updated_task,
-        updated_wedding,
+        "Contribution recorded successfully".to_string(),
+        updated_item,
+        wedding_view(updated_wedding),
     ))
 }
 
-// Delete Task
+// Delete Registry Item
 #[ic_cdk::update]
-fn delete_task(payload: DeleteTaskPayload) -> Result<(String, Task, Wedding), Message> {
+fn delete_registry_item(
+    payload: DeleteRegistryItemPayload,
+) -> Result<(String, RegistryItem, Wedding), Message> {
     // Fetch the wedding from storage
     let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
@@ -1182,26 +10176,24 @@ fn delete_task(payload: DeleteTaskPayload) -> Result<(String, Task, Wedding), Me
         }
     };
 
-    // Fetch the task from the wedding
-    let task = wedding.tasks.iter().find(|task| task.id == payload.task_id);
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can delete registry items".to_string(),
+        ));
+    }
 
-    let task = match task {
-        Some(task) => task.clone(),
+    // Fetch the registry item from the wedding, by ID or by name
+    let item_index = match find_registry_item(&wedding, payload.item_id, &payload.item_name) {
+        Some(index) => index,
         None => {
-            return Err(Message::Error("Task not found".to_string()));
+            return Err(Message::Error("Registry item not found".to_string()));
         }
     };
+    let item = wedding.registry[item_index].clone();
 
-    // Remove the task from the wedding's tasks
+    // Remove the item from the wedding's registry
     let mut updated_wedding = wedding.clone();
-    let updated_tasks: Vec<Task> = updated_wedding
-        .tasks
-        .iter()
-        .filter(|task| task.id != payload.task_id)
-        .cloned()
-        .collect();
-
-    updated_wedding.tasks = updated_tasks;
+    updated_wedding.registry.remove(item_index);
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
@@ -1212,26 +10204,26 @@ fn delete_task(payload: DeleteTaskPayload) -> Result<(String, Task, Wedding), Me
 
     // Return success
     Ok((
-        "Task deleted successfully".to_string(),
-        task,
-        updated_wedding,
+        "Registry item deleted successfully".to_string(),
+        item,
+        wedding_view(updated_wedding),
     ))
 }
 
-// Get Task List
+// Get Registry Items
 #[ic_cdk::query]
-fn get_task_list(wedding_id: u64) -> Result<Vec<Task>, Message> {
+fn get_registry_items(wedding_id: u64) -> Result<Vec<RegistryItem>, Message> {
     WEDDING_STORAGE.with(|storage| {
         let wedding = storage.borrow().get(&wedding_id);
 
         match wedding {
             Some(wedding) => {
-                if wedding.tasks.is_empty() {
+                if wedding.registry.is_empty() {
                     Err(Message::Error(
-                        "No tasks found for this wedding".to_string(),
+                        "No registry items found for this wedding".to_string(),
                     ))
                 } else {
-                    Ok(wedding.tasks.clone())
+                    Ok(redact_registry_purchasers(wedding).registry)
                 }
             }
             None => Err(Message::WeddingNotFound(
@@ -1241,19 +10233,117 @@ fn get_task_list(wedding_id: u64) -> Result<Vec<Task>, Message> {
     })
 }
 
-// Get Task Details
+// Get Registry Item Details
 #[ic_cdk::query]
-fn get_task_details(wedding_id: u64, task_id: u64) -> Result<Task, Message> {
+fn get_registry_item_details(wedding_id: u64, item_name: String) -> Result<RegistryItem, Message> {
     WEDDING_STORAGE.with(|storage| {
         let wedding = storage.borrow().get(&wedding_id);
 
         match wedding {
             Some(wedding) => {
-                let task = wedding.tasks.iter().find(|task| task.id == task_id);
+                let wedding = redact_registry_purchasers(wedding);
+                let item = wedding.registry.iter().find(|item| item.name == item_name);
 
-                match task {
-                    Some(task) => Ok(task.clone()),
-                    None => Err(Message::Error("Task not found".to_string())),
+                match item {
+                    Some(item) => Ok(item.clone()),
+                    None => Err(Message::Error("Registry item not found".to_string())),
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Get Registry Fund Progress
+#[ic_cdk::query]
+fn get_registry_fund_progress(wedding_id: u64) -> Result<Vec<FundProgress>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                let funds: Vec<FundProgress> = redact_registry_purchasers(wedding)
+                    .registry
+                    .iter()
+                    .filter(|item| item.kind == RegistryItemKind::Fund)
+                    .map(|fund| FundProgress {
+                        item_id: fund.id,
+                        name: fund.name.clone(),
+                        target_amount: fund.price,
+                        amount_raised: fund.amount_funded,
+                        contributor_count: fund.contributions.len() as u64,
+                        experiences: fund.experiences.clone(),
+                        contributions: fund.contributions.clone(),
+                    })
+                    .collect();
+
+                if funds.is_empty() {
+                    Err(Message::Error(
+                        "No registry funds found for this wedding".to_string(),
+                    ))
+                } else {
+                    Ok(funds)
+                }
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Get Contributions awaiting a thank-you
+#[ic_cdk::query]
+fn get_contributions_needing_thanks(wedding_id: u64) -> Result<Vec<PendingThankYou>, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                if !is_wedding_owner(&wedding) {
+                    return Err(Message::UnauthorizedAction(
+                        "Only the couple can view pending thank-yous".to_string(),
+                    ));
+                }
+
+                Ok(redact_registry_purchasers(wedding)
+                    .registry
+                    .iter()
+                    .flat_map(|item| {
+                        item.contributions
+                            .iter()
+                            .filter(|contribution| !contribution.thanked)
+                            .map(|contribution| PendingThankYou {
+                                item_id: item.id,
+                                item_name: item.name.clone(),
+                                contribution: contribution.clone(),
+                            })
+                    })
+                    .collect())
+            }
+            None => Err(Message::WeddingNotFound(
+                "Wedding with the provided ID not found".to_string(),
+            )),
+        }
+    })
+}
+
+// Get Registry Item Details by ID
+#[ic_cdk::query]
+fn get_registry_item_by_id(wedding_id: u64, item_id: u64) -> Result<RegistryItem, Message> {
+    WEDDING_STORAGE.with(|storage| {
+        let wedding = storage.borrow().get(&wedding_id);
+
+        match wedding {
+            Some(wedding) => {
+                let wedding = redact_registry_purchasers(wedding);
+                let item = wedding.registry.iter().find(|item| item.id == item_id);
+
+                match item {
+                    Some(item) => Ok(item.clone()),
+                    None => Err(Message::Error("Registry item not found".to_string())),
                 }
             }
             None => Err(Message::WeddingNotFound(
@@ -1264,16 +10354,14 @@ fn get_task_details(wedding_id: u64, task_id: u64) -> Result<Task, Message> {
 }
 
 /*
- * Registry Management
+ * Gift Ledger
  */
 
-// Add Registry Item
+// Collect registry purchases and contributions into the gift ledger
 #[ic_cdk::update]
-fn add_registry_item(
-    payload: AddRegistryItemPayload,
-) -> Result<(String, RegistryItem, Wedding), Message> {
+fn sync_gift_ledger(wedding_id: u64) -> Result<(Vec<Gift>, Message), Message> {
     // Fetch the wedding from storage
-    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
+    let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&wedding_id));
 
     // Validate wedding existence
     let wedding = match wedding {
@@ -1281,54 +10369,128 @@ fn add_registry_item(
         None => {
             return Err(Message::WeddingNotFound(format!(
                 "Wedding with ID {} not found",
-                payload.wedding_id
+                wedding_id
             )))
         }
     };
 
-    // Check if the item already exists
-    let item_exists = wedding
-        .registry
-        .iter()
-        .any(|item| item.name == payload.name);
-
-    if item_exists {
-        return Err(Message::Error("Registry item already exists".to_string()));
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can manage the gift ledger".to_string(),
+        ));
     }
 
-    // Create the new registry item
-    let registry_item = RegistryItem {
-        name: payload.name.clone(),
-        description: payload.description.clone(),
-        price: payload.price,
-        status: "available".to_string(),
-        purchased_by: "".to_string(),
-    };
+    if wedding.hide_registry_purchasers && !wedding_has_passed(&wedding) {
+        return Err(Message::Error(
+            "Registry purchasers are hidden until after the wedding".to_string(),
+        ));
+    }
 
-    // Update the wedding's registry
     let mut updated_wedding = wedding.clone();
-    updated_wedding.registry.push(registry_item.clone());
+    let mut new_gifts = Vec::new();
+
+    for item in wedding.registry.iter() {
+        let already_recorded = |source: GiftSource, record_id: Option<u64>| {
+            wedding.gifts.iter().any(|gift| {
+                gift.source == source
+                    && gift.registry_item_id == Some(item.id)
+                    && gift.source_record_id == record_id
+            })
+        };
+
+        // Purchases, including items marked purchased before purchase records existed
+        for purchase in item.purchases.iter() {
+            if !already_recorded(GiftSource::RegistryPurchase, Some(purchase.id)) {
+                new_gifts.push(Gift {
+                    id: generate_uuid(),
+                    giver: purchase.purchased_by.clone(),
+                    guest_email: find_guest_email_for(&wedding, &purchase.purchased_by),
+                    description: item.name.clone(),
+                    amount: item.price.saturating_mul(purchase.quantity),
+                    source: GiftSource::RegistryPurchase,
+                    registry_item_id: Some(item.id),
+                    source_record_id: Some(purchase.id),
+                    gift_date: purchase.date.clone(),
+                    thank_you_status: ThankYouStatus::Pending,
+                    thanked_date: None,
+                });
+            }
+        }
+        if item.purchases.is_empty()
+            && !item.purchased_by.is_empty()
+            && !already_recorded(GiftSource::RegistryPurchase, None)
+        {
+            new_gifts.push(Gift {
+                id: generate_uuid(),
+                giver: item.purchased_by.clone(),
+                guest_email: find_guest_email_for(&wedding, &item.purchased_by),
+                description: item.name.clone(),
+                amount: item.price,
+                source: GiftSource::RegistryPurchase,
+                registry_item_id: Some(item.id),
+                source_record_id: None,
+                gift_date: "".to_string(),
+                thank_you_status: ThankYouStatus::Pending,
+                thanked_date: None,
+            });
+        }
+
+        // Contributions towards group gifts and funds
+        for contribution in item.contributions.iter() {
+            if !already_recorded(GiftSource::RegistryContribution, Some(contribution.id)) {
+                new_gifts.push(Gift {
+                    id: generate_uuid(),
+                    giver: contribution.contributor.clone(),
+                    guest_email: find_guest_email_for(&wedding, &contribution.contributor),
+                    description: format!("Contribution to {}", item.name),
+                    amount: contribution.amount,
+                    source: GiftSource::RegistryContribution,
+                    registry_item_id: Some(item.id),
+                    source_record_id: Some(contribution.id),
+                    gift_date: contribution.date.clone(),
+                    thank_you_status: if contribution.thanked {
+                        ThankYouStatus::Sent
+                    } else {
+                        ThankYouStatus::Pending
+                    },
+                    thanked_date: None,
+                });
+            }
+        }
+    }
+
+    let added = new_gifts.len();
+    updated_wedding.gifts.extend(new_gifts);
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
         storage
             .borrow_mut()
-            .insert(payload.wedding_id, updated_wedding.clone());
+            .insert(wedding_id, updated_wedding.clone());
     });
 
-    // Return success
     Ok((
-        "Registry item added successfully".to_string(),
-        registry_item,
-        updated_wedding,
+        updated_wedding.gifts,
+        Message::Success(format!("{} gifts added to the ledger", added)),
     ))
 }
 
-// Update Registry Item Status
+// Add a gift that was given outside the registry
 #[ic_cdk::update]
-fn update_registry_item_status(
-    payload: UpdateRegistryItemStatusPayload,
-) -> Result<(String, RegistryItem, Wedding), Message> {
+fn add_off_registry_gift(payload: AddGiftPayload) -> Result<(Gift, Message), Message> {
+    // Validate the user input
+    if payload.giver.is_empty() || payload.description.is_empty() {
+        return Err(Message::InvalidInput(
+            "Giver and gift description are required".to_string(),
+        ));
+    }
+
+    if parse_date(&payload.gift_date).is_none() {
+        return Err(Message::InvalidDate(
+            "Gift date must be in YYYY-MM-DD format".to_string(),
+        ));
+    }
+
     // Fetch the wedding from storage
     let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
@@ -1343,58 +10505,48 @@ fn update_registry_item_status(
         }
     };
 
-    // Fetch the registry item from the wedding
-    let item = wedding
-        .registry
-        .iter()
-        .find(|item| item.name == payload.item_name);
-
-    let item = match item {
-        Some(item) => item.clone(),
-        None => {
-            return Err(Message::Error("Registry item not found".to_string()));
-        }
-    };
-
-    // Update the registry item status
-    let mut updated_item = item.clone();
-    updated_item.status = payload.status.clone();
-    updated_item.purchased_by = payload.purchased_by.clone();
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can manage the gift ledger".to_string(),
+        ));
+    }
 
-    // Update the wedding's registry
-    let mut updated_wedding = wedding.clone();
-    updated_wedding.registry = updated_wedding
-        .registry
-        .iter()
-        .map(|item| {
-            if item.name == payload.item_name {
-                updated_item.clone()
-            } else {
-                item.clone()
-            }
-        })
-        .collect();
+    let gift = Gift {
+        id: generate_uuid(),
+        giver: payload.giver.clone(),
+        guest_email: payload
+            .guest_email
+            .clone()
+            .or_else(|| find_guest_email_for(&wedding, &payload.giver)),
+        description: payload.description.clone(),
+        amount: payload.amount,
+        source: GiftSource::OffRegistry,
+        registry_item_id: None,
+        source_record_id: None,
+        gift_date: payload.gift_date.clone(),
+        thank_you_status: ThankYouStatus::Pending,
+        thanked_date: None,
+    };
+
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.gifts.push(gift.clone());
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
         storage
             .borrow_mut()
-            .insert(payload.wedding_id, updated_wedding.clone());
+            .insert(payload.wedding_id, updated_wedding);
     });
 
-    // Return success
     Ok((
-        "Registry item status updated successfully".to_string(),
-        updated_item,
-        updated_wedding,
+        gift,
+        Message::Success("Gift added to the ledger".to_string()),
     ))
 }
 
-// Delete Registry Item
+// Update the thank-you status of a gift
 #[ic_cdk::update]
-fn delete_registry_item(
-    payload: DeleteRegistryItemPayload,
-) -> Result<(String, RegistryItem, Wedding), Message> {
+fn update_gift_thank_you(payload: UpdateGiftThankYouPayload) -> Result<(Gift, Message), Message> {
     // Fetch the wedding from storage
     let wedding = WEDDING_STORAGE.with(|storage| storage.borrow().get(&payload.wedding_id));
 
@@ -1409,59 +10561,95 @@ fn delete_registry_item(
         }
     };
 
-    // Fetch the registry item from the wedding
-    let item = wedding
-        .registry
-        .iter()
-        .find(|item| item.name == payload.item_name);
+    if !is_wedding_owner(&wedding) {
+        return Err(Message::UnauthorizedAction(
+            "Only the couple can manage the gift ledger".to_string(),
+        ));
+    }
 
-    let item = match item {
-        Some(item) => item.clone(),
+    if let Some(thanked_date) = &payload.thanked_date {
+        if parse_date(thanked_date).is_none() {
+            return Err(Message::InvalidDate(
+                "Thank-you date must be in YYYY-MM-DD format".to_string(),
+            ));
+        }
+    }
+
+    // Fetch the gift from the ledger
+    let gift_index = match wedding
+        .gifts
+        .iter()
+        .position(|gift| gift.id == payload.gift_id)
+    {
+        Some(index) => index,
         None => {
-            return Err(Message::Error("Registry item not found".to_string()));
+            return Err(Message::Error("Gift not found".to_string()));
         }
     };
 
-    // Remove the item from the wedding's registry
-    let mut updated_wedding = wedding.clone();
-    let updated_registry: Vec<RegistryItem> = updated_wedding
-        .registry
-        .iter()
-        .filter(|item| item.name != payload.item_name)
-        .cloned()
-        .collect();
+    // Update the thank-you status
+    let mut updated_gift = wedding.gifts[gift_index].clone();
+    updated_gift.thank_you_status = payload.thank_you_status.clone();
+    updated_gift.thanked_date = match payload.thank_you_status {
+        ThankYouStatus::Sent => payload
+            .thanked_date
+            .clone()
+            .or_else(|| Some(current_datetime().date().to_string())),
+        _ => None,
+    };
+    if payload.guest_email.is_some() {
+        updated_gift.guest_email = payload.guest_email.clone();
+    }
 
-    updated_wedding.registry = updated_registry;
+    let mut updated_wedding = wedding.clone();
+    updated_wedding.gifts[gift_index] = updated_gift.clone();
+
+    // Keep the registry contribution's thank-you flag in step
+    if updated_gift.source == GiftSource::RegistryContribution {
+        for contribution in updated_wedding
+            .registry
+            .iter_mut()
+            .flat_map(|item| item.contributions.iter_mut())
+        {
+            if Some(contribution.id) == updated_gift.source_record_id {
+                contribution.thanked = updated_gift.thank_you_status == ThankYouStatus::Sent;
+            }
+        }
+    }
 
     // Save the updated wedding details
     WEDDING_STORAGE.with(|storage| {
         storage
             .borrow_mut()
-            .insert(payload.wedding_id, updated_wedding.clone());
+            .insert(payload.wedding_id, updated_wedding);
     });
 
-    // Return success
     Ok((
-        "Registry item deleted successfully".to_string(),
-        item,
-        updated_wedding,
+        updated_gift,
+        Message::Success("Gift thank-you status updated".to_string()),
     ))
 }
 
-// Get Registry Items
+// Get Gift Ledger
 #[ic_cdk::query]
-fn get_registry_items(wedding_id: u64) -> Result<Vec<RegistryItem>, Message> {
+fn get_gift_ledger(wedding_id: u64) -> Result<Vec<Gift>, Message> {
     WEDDING_STORAGE.with(|storage| {
         let wedding = storage.borrow().get(&wedding_id);
 
         match wedding {
             Some(wedding) => {
-                if wedding.registry.is_empty() {
+                if !is_wedding_owner(&wedding) {
+                    return Err(Message::UnauthorizedAction(
+                        "Only the couple can view the gift ledger".to_string(),
+                    ));
+                }
+
+                if wedding.gifts.is_empty() {
                     Err(Message::Error(
-                        "No registry items found for this wedding".to_string(),
+                        "No gifts recorded for this wedding".to_string(),
                     ))
                 } else {
-                    Ok(wedding.registry.clone())
+                    Ok(wedding.gifts.clone())
                 }
             }
             None => Err(Message::WeddingNotFound(
@@ -1471,20 +10659,33 @@ fn get_registry_items(wedding_id: u64) -> Result<Vec<RegistryItem>, Message> {
     })
 }
 
-// Get Registry Item Details
+// Get Outstanding Thank-yous, oldest gift first
 #[ic_cdk::query]
-fn get_registry_item_details(wedding_id: u64, item_name: String) -> Result<RegistryItem, Message> {
+fn get_outstanding_thank_yous(wedding_id: u64) -> Result<Vec<Gift>, Message> {
     WEDDING_STORAGE.with(|storage| {
         let wedding = storage.borrow().get(&wedding_id);
 
         match wedding {
             Some(wedding) => {
-                let item = wedding.registry.iter().find(|item| item.name == item_name);
-
-                match item {
-                    Some(item) => Ok(item.clone()),
-                    None => Err(Message::Error("Registry item not found".to_string())),
+                if !is_wedding_owner(&wedding) {
+                    return Err(Message::UnauthorizedAction(
+                        "Only the couple can view the gift ledger".to_string(),
+                    ));
                 }
+
+                let mut outstanding: Vec<Gift> = wedding
+                    .gifts
+                    .iter()
+                    .filter(|gift| gift.thank_you_status != ThankYouStatus::Sent)
+                    .cloned()
+                    .collect();
+                // Gifts with a missing or unreadable date go last
+                outstanding.sort_by_key(|gift| {
+                    let date = parse_gift_date(&gift.gift_date);
+                    (date.is_none(), date)
+                });
+
+                Ok(outstanding)
             }
             None => Err(Message::WeddingNotFound(
                 "Wedding with the provided ID not found".to_string(),
@@ -1493,5 +10694,944 @@ fn get_registry_item_details(wedding_id: u64, item_name: String) -> Result<Regis
     })
 }
 
+/*
+ * Checklist Templates
+ */
+
+// Add a checklist template (canister controllers only)
+#[ic_cdk::update]
+fn add_checklist_template(
+    payload: ChecklistTemplatePayload,
+) -> Result<(ChecklistTemplate, Message), Message> {
+    if !ic_cdk::api::is_controller(&caller()) {
+        return Err(Message::UnauthorizedAction(
+            "Only the canister's controllers can add checklist templates".to_string(),
+        ));
+    }
+
+    // Validate the user input
+    if payload.name.trim().is_empty() || payload.items.is_empty() {
+        return Err(Message::InvalidInput(
+            "A template needs a name and at least one item".to_string(),
+        ));
+    }
+    let mut titles: Vec<&str> = payload.items.iter().map(|item| item.title.trim()).collect();
+    titles.sort_unstable();
+    let untitled = titles.iter().any(|title| title.is_empty());
+    titles.dedup();
+    if untitled || titles.len() != payload.items.len() {
+        return Err(Message::InvalidInput(
+            "Every item needs its own title".to_string(),
+        ));
+    }
+    if payload.items.iter().any(|item| {
+        item.days_before > MAX_CHECKLIST_DAYS_BEFORE || item.duration_days > MAX_TASK_DURATION_DAYS
+    }) {
+        return Err(Message::InvalidInput(format!(
+            "Items can be scheduled at most {} days before the wedding and take at most {} days",
+            MAX_CHECKLIST_DAYS_BEFORE, MAX_TASK_DURATION_DAYS
+        )));
+    }
+
+    let template = ChecklistTemplate {
+        id: generate_uuid(),
+        name: payload.name.trim().to_string(),
+        description: payload.description.clone(),
+        items: payload
+            .items
+            .iter()
+            .map(|item| ChecklistItem {
+                title: item.title.trim().to_string(),
+                ..item.clone()
+            })
+            .collect(),
+        built_in: false,
+    };
+
+    // Templates are stored whole, so they must fit in a storage slot
+    let encoded_size = Encode!(&template).map_or(usize::MAX, |bytes| bytes.len());
+    if encoded_size > ChecklistTemplate::MAX_SIZE as usize {
+        return Err(Message::InvalidInput(format!(
+            "The template is too large to store; keep it under {} bytes",
+            ChecklistTemplate::MAX_SIZE
+        )));
+    }
+
+    TEMPLATE_STORAGE.with(|storage| {
+        storage.borrow_mut().insert(template.id, template.clone());
+    });
+
+    Ok((
+        template,
+        Message::Success("Checklist template added successfully".to_string()),
+    ))
+}
+
+// Delete a checklist template (canister controllers only; built-in templates stay)
+#[ic_cdk::update]
+fn delete_checklist_template(template_id: u64) -> Result<(ChecklistTemplate, Message), Message> {
+    if !ic_cdk::api::is_controller(&caller()) {
+        return Err(Message::UnauthorizedAction(
+            "Only the canister's controllers can delete checklist templates".to_string(),
+        ));
+    }
+
+    let template = match find_checklist_template(Some(template_id)) {
+        Some(template) => template,
+        None => {
+            return Err(Message::Error("Checklist template not found".to_string()));
+        }
+    };
+    if template.built_in {
+        return Err(Message::Error(
+            "Built-in templates can't be deleted".to_string(),
+        ));
+    }
+
+    TEMPLATE_STORAGE.with(|storage| storage.borrow_mut().remove(&template_id));
+
+    Ok((
+        template,
+        Message::Success("Checklist template deleted successfully".to_string()),
+    ))
+}
+
+// Get all checklist templates
+#[ic_cdk::query]
+fn get_checklist_templates() -> Result<Vec<ChecklistTemplate>, Message> {
+    Ok(TEMPLATE_STORAGE.with(|storage| {
+        storage
+            .borrow()
+            .iter()
+            .map(|(_, template)| template)
+            .collect()
+    }))
+}
+
+/*
+ * Canister Upgrades
+ */
+
+// Store the built-in checklist templates and start the overdue sweep on install
+#[ic_cdk::init]
+fn init() {
+    seed_checklist_templates();
+    start_overdue_sweep();
+}
+
+// Convert a wedding from the original storage layout, giving its guests, timeline and
+// registry items their own IDs, and tasks without one a fresh ID
+fn migrate_legacy_wedding(legacy: LegacyWedding, owner: Principal) -> Wedding {
+    let timeline = legacy
+        .timeline
+        .into_iter()
+        .map(|item| TimelineItem {
+            id: generate_uuid(),
+            wedding_id: legacy.id,
+            time: item.time,
+            duration_minutes: 0,
+            description: item.description,
+            responsible: item.responsible,
+            status: item.status,
+            started_at: None,
+            finished_at: None,
+            delay_minutes: 0,
+        })
+        .collect();
+
+    let tasks = legacy
+        .tasks
+        .into_iter()
+        .map(|task| Task {
+            // The ID counter started at 0, so a 0 here can't be told apart from a missing ID
+            id: if task.id == 0 {
+                generate_uuid()
+            } else {
+                task.id
+            },
+            title: task.title,
+            description: task.description,
+            deadline: task.deadline,
+            assigned_to: task.assigned_to,
+            status: task.status,
+            budget: task.budget,
+            duration_days: 0,
+            depends_on: Vec::new(),
+            checklist: None,
+        })
+        .collect();
+
+    // Legacy guests were only added by their own RSVP, so that answer goes into their history
+    // and the RSVP deadline doesn't treat them as guests who never responded. When they
+    // answered wasn't stored
+    let guest_list = legacy
+        .guest_list
+        .into_iter()
+        .map(|guest| Guest {
+            id: generate_uuid(),
+            name: guest.name,
+            guest_email: normalise_email(&guest.guest_email),
+            rsvp_history: vec![RsvpResponse {
+                status: guest.rsvp_status.clone(),
+                plus_one: guest.plus_one,
+                plus_one_name: None,
+                meal_choice: "".to_string(),
+                dietary_restrictions: guest.dietary_restrictions.clone(),
+                responded_at: "".to_string(),
+            }],
+            rsvp_status: guest.rsvp_status,
+            dietary_restrictions: guest.dietary_restrictions,
+            plus_one: guest.plus_one,
+            table_assignment: guest.table_assignment,
+            ..Default::default()
+        })
+        .collect();
+
+    // Legacy items were single gifts; a purchased one keeps its purchaser for the gift ledger
+    let registry = legacy
+        .registry
+        .into_iter()
+        .map(|item| RegistryItem {
+            id: generate_uuid(),
+            kind: RegistryItemKind::Item,
+            name: item.name,
+            description: item.description,
+            price: item.price,
+            quantity_desired: 1,
+            quantity_received: if item.status == "purchased" { 1 } else { 0 },
+            status: item.status,
+            purchased_by: item.purchased_by,
+            ..Default::default()
+        })
+        .collect();
+
+    Wedding {
+        id: legacy.id,
+        owner,
+        couple_names: legacy.couple_names,
+        date: legacy.date,
+        budget: legacy.budget,
+        location: legacy.location,
+        guest_count: legacy.guest_count,
+        vendors: legacy.vendors,
+        timeline,
+        timeline_shifts: Vec::new(),
+        vendor_notifications: Vec::new(),
+        tasks,
+        guest_list,
+        households: Vec::new(),
+        events: Vec::new(),
+        invitations: Vec::new(),
+        code_salt: "".to_string(),
+        waitlist: Vec::new(),
+        registry,
+        status: legacy.status,
+        rsvp_deadline: None,
+        rsvp_reminder_days: Vec::new(),
+        rsvp_deadline_action: RsvpDeadlineAction::default(),
+        rsvps_closed: false,
+        rsvp_reminders: Vec::new(),
+        meal_options: Vec::new(),
+        catering_lock_date: None,
+        check_in_staff: Vec::new(),
+        planners: Vec::new(),
+        room_blocks: Vec::new(),
+        transport_runs: Vec::new(),
+        hide_registry_purchasers: false,
+        gifts: Vec::new(),
+    }
+}
+
+// Move weddings out of the original storage. Legacy weddings had no owner, so the
+// controller running the upgrade becomes the owner of each of them
+fn migrate_legacy_weddings() {
+    let legacy_weddings: Vec<LegacyWedding> = LEGACY_WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow()
+            .iter()
+            .map(|(_, wedding)| wedding.clone())
+            .collect()
+    });
+
+    let owner = caller();
+    for legacy in legacy_weddings {
+        let wedding_id = legacy.id;
+        let wedding = migrate_legacy_wedding(legacy, owner);
+
+        WEDDING_STORAGE.with(|storage| {
+            storage.borrow_mut().insert(wedding_id, wedding);
+        });
+        LEGACY_WEDDING_STORAGE.with(|storage| {
+            storage.borrow_mut().remove(&wedding_id);
+        });
+    }
+}
+
+// Timers are lost on upgrade, so set the overdue sweep, RSVP and registry reservation
+// timers again
+#[ic_cdk::post_upgrade]
+fn post_upgrade() {
+    migrate_legacy_weddings();
+    seed_checklist_templates();
+    start_overdue_sweep();
+
+    let weddings: Vec<Wedding> = WEDDING_STORAGE.with(|storage| {
+        storage
+            .borrow()
+            .iter()
+            .map(|(_, wedding)| wedding.clone())
+            .collect()
+    });
+
+    let now = ic_cdk::api::time();
+    for wedding in weddings.iter() {
+        schedule_rsvp_timers(wedding);
+
+        for item in wedding.registry.iter() {
+            if let (Some(until), "reserved") = (item.reserved_until, item.status.as_str()) {
+                let wedding_id = wedding.id;
+                let item_id = item.id;
+                let delay = Duration::from_nanos(until.saturating_sub(now));
+                ic_cdk_timers::set_timer(delay, move || {
+                    release_expired_reservation(wedding_id, item_id)
+                });
+            }
+        }
+    }
+}
+
 // Export Candid interface
 ic_cdk::export_candid!();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrated_wedding_gets_ids_and_defaults() {
+        // A canister with legacy weddings has already handed out IDs
+        generate_uuid();
+
+        let legacy = LegacyWedding {
+            id: 3,
+            couple_names: vec!["Ann".to_string(), "Ben".to_string()],
+            date: "2026-06-20".to_string(),
+            timeline: vec![
+                LegacyTimelineItem {
+                    time: "14:00".to_string(),
+                    description: "Ceremony".to_string(),
+                    ..Default::default()
+                },
+                LegacyTimelineItem {
+                    time: "18:00".to_string(),
+                    description: "Dinner".to_string(),
+                    ..Default::default()
+                },
+            ],
+            tasks: vec![
+                LegacyTask {
+                    id: 0,
+                    title: "Book venue".to_string(),
+                    ..Default::default()
+                },
+                LegacyTask {
+                    id: 42,
+                    title: "Send invites".to_string(),
+                    ..Default::default()
+                },
+            ],
+            guest_list: vec![
+                LegacyGuest {
+                    name: "Cara Smith".to_string(),
+                    guest_email: " Cara@Example.com ".to_string(),
+                    rsvp_status: "confirmed".to_string(),
+                    plus_one: true,
+                    ..Default::default()
+                },
+                LegacyGuest {
+                    name: "Eve Jones".to_string(),
+                    guest_email: "eve@example.com".to_string(),
+                    rsvp_status: "pending".to_string(),
+                    ..Default::default()
+                },
+            ],
+            registry: vec![LegacyRegistryItem {
+                name: "Toaster".to_string(),
+                price: 40,
+                status: "purchased".to_string(),
+                purchased_by: "Dan".to_string(),
+                ..Default::default()
+            }],
+            status: "planning".to_string(),
+            ..Default::default()
+        };
+
+        let wedding = migrate_legacy_wedding(legacy, Principal::anonymous());
+
+        assert_eq!(wedding.id, 3);
+        assert_eq!(wedding.owner, Principal::anonymous());
+        assert!(wedding
+            .timeline
+            .iter()
+            .all(|item| item.id != 0 && item.wedding_id == 3));
+        assert_ne!(wedding.timeline[0].id, wedding.timeline[1].id);
+        assert_ne!(wedding.tasks[0].id, 0);
+        assert_eq!(wedding.tasks[1].id, 42);
+        assert_eq!(wedding.guest_list[0].guest_email, "cara@example.com");
+        assert_ne!(wedding.guest_list[0].id, 0);
+        assert!(wedding.guest_list[0].plus_one);
+        assert_eq!(wedding.guest_list[0].rsvp_history[0].status, "confirmed");
+
+        // A pending legacy guest already responded, so the RSVP deadline leaves them alone
+        let pending = &wedding.guest_list[1];
+        assert_eq!(pending.rsvp_status, "pending");
+        assert_eq!(pending.rsvp_history.len(), 1);
+        assert!(!awaiting_rsvp(pending));
+
+        let item = &wedding.registry[0];
+        assert_eq!(item.quantity_desired, 1);
+        assert_eq!(item.quantity_received, 1);
+        assert_eq!(registry_item_remaining(item), 0);
+        assert_eq!(item.purchased_by, "Dan");
+    }
+
+    #[test]
+    fn parse_csv_reads_quoted_fields_and_skips_blank_lines() {
+        let data = "name,email\r\n\"Smith, Ann\",ann@example.com\n\n\"Say \"\"hi\"\"\",\nlast,row";
+        assert_eq!(
+            parse_csv(data),
+            vec![
+                vec!["name".to_string(), "email".to_string()],
+                vec!["Smith, Ann".to_string(), "ann@example.com".to_string()],
+                vec!["Say \"hi\"".to_string(), "".to_string()],
+                vec!["last".to_string(), "row".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_csv_keeps_newlines_inside_quotes() {
+        assert_eq!(
+            parse_csv("\"1 Main St\nFlat 2\",x\n"),
+            vec![vec!["1 Main St\nFlat 2".to_string(), "x".to_string()]]
+        );
+    }
+
+    #[test]
+    fn csv_field_escapes_formulas_and_quotes() {
+        assert_eq!(csv_field("Ann"), "Ann");
+        assert_eq!(csv_field("=SUM(A1)"), "'=SUM(A1)");
+        assert_eq!(csv_field("@cmd"), "'@cmd");
+        assert_eq!(csv_field("-1,2"), "\"'-1,2\"");
+        assert_eq!(csv_field("Say \"hi\""), "\"Say \"\"hi\"\"\"");
+        assert_eq!(
+            parse_csv(&csv_field("a,\"b\"")),
+            vec![vec!["a,\"b\"".to_string()]]
+        );
+    }
+
+    fn guest(name: &str, email: &str) -> Guest {
+        Guest {
+            name: name.to_string(),
+            guest_email: email.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("jonathan", "johnathan"), 1);
+    }
+
+    #[test]
+    fn duplicate_reason_matches_email_and_similar_names() {
+        assert_eq!(
+            duplicate_reason(
+                &guest("Ann Smith", " Ann@Example.com"),
+                &guest("Annie", "ann@example.com")
+            ),
+            Some("Same email".to_string())
+        );
+        assert_eq!(
+            duplicate_reason(&guest("Smith, Ann", ""), &guest("ann smith", "")),
+            Some("Same name".to_string())
+        );
+        assert_eq!(
+            duplicate_reason(&guest("A. Smith", ""), &guest("Ann Smith", "")),
+            Some("Same surname and initial".to_string())
+        );
+        assert_eq!(
+            duplicate_reason(&guest("Jonathan Smith", ""), &guest("Johnathan Smith", "")),
+            Some("Similar name".to_string())
+        );
+    }
+
+    #[test]
+    fn duplicate_reason_ignores_different_guests() {
+        assert_eq!(
+            duplicate_reason(&guest("Ann Smith", ""), &guest("Ben Smith", "")),
+            None
+        );
+        assert_eq!(duplicate_reason(&guest("", ""), &guest("", "")), None);
+        assert_eq!(
+            duplicate_reason(&guest("Al Li", ""), &guest("Al Lu", "")),
+            None
+        );
+    }
+
+    fn wedding(date: &str) -> Wedding {
+        Wedding {
+            id: 1,
+            owner: Principal::anonymous(),
+            couple_names: vec!["Ann".to_string(), "Ben".to_string()],
+            date: date.to_string(),
+            budget: 0,
+            location: "".to_string(),
+            guest_count: 0,
+            vendors: Vec::new(),
+            timeline: Vec::new(),
+            timeline_shifts: Vec::new(),
+            vendor_notifications: Vec::new(),
+            tasks: Vec::new(),
+            guest_list: Vec::new(),
+            households: Vec::new(),
+            events: Vec::new(),
+            invitations: Vec::new(),
+            code_salt: "".to_string(),
+            waitlist: Vec::new(),
+            registry: Vec::new(),
+            status: "planning".to_string(),
+            rsvp_deadline: None,
+            rsvp_reminder_days: Vec::new(),
+            rsvp_deadline_action: RsvpDeadlineAction::default(),
+            rsvps_closed: false,
+            rsvp_reminders: Vec::new(),
+            meal_options: Vec::new(),
+            catering_lock_date: None,
+            check_in_staff: Vec::new(),
+            planners: Vec::new(),
+            room_blocks: Vec::new(),
+            transport_runs: Vec::new(),
+            hide_registry_purchasers: false,
+            gifts: Vec::new(),
+        }
+    }
+
+    fn timeline_item(
+        id: u64,
+        time: &str,
+        duration_minutes: u64,
+        responsible: &str,
+    ) -> TimelineItem {
+        TimelineItem {
+            id,
+            wedding_id: 1,
+            time: time.to_string(),
+            duration_minutes,
+            description: format!("Item {}", id),
+            responsible: responsible.to_string(),
+            status: "pending".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn timeline_items_overlap_for_the_same_responsible_party() {
+        let wedding = wedding("2026-06-20");
+        let ceremony = timeline_item(1, "14:00", 60, "Photographer");
+        let portraits = timeline_item(2, "14:30", 30, " photographer ");
+        let dinner = timeline_item(3, "15:00", 90, "Photographer");
+        let same_day = timeline_item(4, "2026-06-20 14:15", 15, "Photographer");
+
+        assert!(timeline_items_overlap(&wedding, &ceremony, &portraits));
+        assert!(timeline_items_overlap(&wedding, &ceremony, &same_day));
+        // Back-to-back items don't overlap
+        assert!(!timeline_items_overlap(&wedding, &ceremony, &dinner));
+        assert!(!timeline_items_overlap(&wedding, &ceremony, &ceremony));
+    }
+
+    #[test]
+    fn timeline_items_overlap_ignores_other_people_and_unreadable_items() {
+        let wedding = wedding("2026-06-20");
+        let ceremony = timeline_item(1, "14:00", 60, "Photographer");
+
+        assert!(!timeline_items_overlap(
+            &wedding,
+            &ceremony,
+            &timeline_item(2, "14:00", 60, "DJ")
+        ));
+        assert!(!timeline_items_overlap(
+            &wedding,
+            &timeline_item(3, "14:00", 60, ""),
+            &timeline_item(4, "14:00", 60, "")
+        ));
+        assert!(!timeline_items_overlap(
+            &wedding,
+            &ceremony,
+            &timeline_item(5, "2pm", 60, "Photographer")
+        ));
+        // Items at the same time overlap even without a duration
+        assert!(timeline_items_overlap(
+            &wedding,
+            &timeline_item(6, "14:00", 0, "Photographer"),
+            &timeline_item(7, "14:00", 0, "Photographer")
+        ));
+        // Out-of-range durations are treated as unreadable rather than panicking
+        assert!(!timeline_items_overlap(
+            &wedding,
+            &ceremony,
+            &timeline_item(8, "13:00", u64::MAX, "Photographer")
+        ));
+    }
+
+    #[test]
+    fn delay_timeline_moves_the_item_and_later_pending_items() {
+        let mut wedding = wedding("2026-06-20");
+        let mut started = timeline_item(4, "16:00", 30, "DJ");
+        started.started_at = Some("2026-06-20 16:00:00".to_string());
+        wedding.timeline = vec![
+            timeline_item(1, "14:00", 60, "Photographer"),
+            timeline_item(2, "15:00", 30, "Caterer"),
+            timeline_item(3, "13:00", 30, "Florist"),
+            started,
+            timeline_item(5, "2026-06-20 23:50", 30, "DJ"),
+        ];
+
+        let shifts = delay_timeline(&mut wedding, 1, 20, true, "2026-06-20 13:55:00");
+
+        let times: Vec<&str> = wedding
+            .timeline
+            .iter()
+            .map(|item| item.time.as_str())
+            .collect();
+        assert_eq!(
+            times,
+            vec!["14:20", "15:20", "13:00", "16:00", "2026-06-21 00:10"]
+        );
+        assert_eq!(wedding.timeline[0].status, "delayed");
+        assert_eq!(wedding.timeline[1].status, "pending");
+        assert_eq!(wedding.timeline[1].delay_minutes, 20);
+        assert_eq!(wedding.timeline[2].delay_minutes, 0);
+
+        let shifted: Vec<u64> = shifts.iter().map(|shift| shift.item_id).collect();
+        assert_eq!(shifted, vec![1, 2, 5]);
+        assert!(shifts
+            .iter()
+            .all(|shift| shift.caused_by == 1 && shift.shifted_at == "2026-06-20 13:55:00"));
+        assert_eq!(wedding.timeline_shifts.len(), 3);
+    }
+
+    #[test]
+    fn delay_timeline_extends_an_item_that_already_started() {
+        let mut wedding = wedding("2026-06-20");
+        let mut ceremony = timeline_item(1, "14:00", 60, "Officiant");
+        ceremony.started_at = Some("2026-06-20 14:00:00".to_string());
+        wedding.timeline = vec![ceremony, timeline_item(2, "15:00", 30, "Caterer")];
+
+        let shifts = delay_timeline(&mut wedding, 1, 15, true, "2026-06-20 14:30:00");
+
+        assert_eq!(wedding.timeline[0].time, "14:00");
+        assert_eq!(wedding.timeline[0].duration_minutes, 75);
+        assert_eq!(wedding.timeline[0].status, "delayed");
+        assert_eq!(wedding.timeline[1].time, "15:15");
+        assert_eq!(shifts.len(), 1);
+    }
+
+    #[test]
+    fn delay_timeline_leaves_parallel_items_and_other_parties_alone() {
+        let mut wedding = wedding("2026-06-20");
+        wedding.timeline = vec![
+            timeline_item(1, "14:00", 30, "Photographer"),
+            timeline_item(2, "14:00", 30, "Florist"),
+            timeline_item(3, "15:00", 30, "Caterer"),
+            timeline_item(4, "16:00", 30, "photographer"),
+        ];
+
+        // A vendor's own delay only moves their own later items
+        let shifts = delay_timeline(&mut wedding, 1, 10, false, "");
+
+        let times: Vec<&str> = wedding
+            .timeline
+            .iter()
+            .map(|item| item.time.as_str())
+            .collect();
+        assert_eq!(times, vec!["14:10", "14:00", "15:00", "16:10"]);
+        assert_eq!(shifts.len(), 2);
+
+        // The couple's delay moves everyone after it, but not items running in parallel
+        delay_timeline(&mut wedding, 1, 10, true, "");
+        let times: Vec<&str> = wedding
+            .timeline
+            .iter()
+            .map(|item| item.time.as_str())
+            .collect();
+        assert_eq!(times, vec!["14:20", "14:00", "15:10", "16:20"]);
+    }
+
+    #[test]
+    fn delay_timeline_ignores_unknown_items_and_unreadable_times() {
+        let mut wedding = wedding("2026-06-20");
+        wedding.timeline = vec![timeline_item(1, "around two", 60, "Photographer")];
+
+        assert!(delay_timeline(&mut wedding, 1, 10, true, "").is_empty());
+        assert!(delay_timeline(&mut wedding, 9, 10, true, "").is_empty());
+        assert_eq!(wedding.timeline[0].time, "around two");
+    }
+
+    fn task(id: u64, depends_on: &[u64]) -> Task {
+        Task {
+            id,
+            title: format!("Task {}", id),
+            status: "pending".to_string(),
+            depends_on: depends_on.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn task_order_puts_dependencies_first() {
+        let tasks = vec![task(1, &[3]), task(2, &[]), task(3, &[2]), task(4, &[1, 2])];
+        let order: Vec<u64> = task_order(&tasks)
+            .unwrap()
+            .into_iter()
+            .map(|index| tasks[index].id)
+            .collect();
+        assert_eq!(order, vec![2, 3, 1, 4]);
+    }
+
+    #[test]
+    fn task_order_skips_missing_dependencies_and_rejects_cycles() {
+        assert_eq!(task_order(&[task(1, &[99])]), Some(vec![0]));
+        assert_eq!(task_order(&[]), Some(Vec::new()));
+        assert_eq!(task_order(&[task(1, &[2]), task(2, &[1])]), None);
+        assert_eq!(task_order(&[task(1, &[1])]), None);
+    }
+
+    fn registry_item(price: u64, quantity_desired: u64) -> RegistryItem {
+        RegistryItem {
+            id: 10,
+            kind: RegistryItemKind::Item,
+            name: "Stand mixer".to_string(),
+            price,
+            quantity_desired,
+            status: "available".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn contributions_stop_at_the_price_of_the_unbought_units() {
+        let mut item = registry_item(100, 2);
+        item.quantity_received = 1;
+
+        assert!(matches!(
+            apply_registry_contribution(&mut item, 101, None, 0),
+            Err(Message::InvalidInput(_))
+        ));
+        assert_eq!(item.amount_funded, 0);
+
+        assert!(apply_registry_contribution(&mut item, 60, None, 0).is_ok());
+        assert_eq!(item.status, "funding");
+        assert!(matches!(
+            apply_registry_contribution(&mut item, 41, None, 0),
+            Err(Message::InvalidInput(_))
+        ));
+
+        assert!(apply_registry_contribution(&mut item, 40, None, 0).is_ok());
+        assert_eq!(item.status, "fulfilled");
+        assert_eq!(registry_item_remaining(&item), 0);
+        assert!(matches!(
+            apply_registry_contribution(&mut item, 1, None, 0),
+            Err(Message::Error(_))
+        ));
+    }
+
+    #[test]
+    fn contributions_respect_reservations_and_experience_caps() {
+        let mut item = registry_item(100, 1);
+        item.status = "reserved".to_string();
+        item.reserved_until = Some(1_000);
+        assert!(matches!(
+            apply_registry_contribution(&mut item, 10, None, 999),
+            Err(Message::Error(_))
+        ));
+        // Once the reservation has lapsed the item takes contributions again
+        assert!(apply_registry_contribution(&mut item, 10, None, 1_000).is_ok());
+
+        let mut fund = registry_item(500, 1);
+        fund.kind = RegistryItemKind::Fund;
+        fund.experiences = vec![FundExperience {
+            id: 7,
+            name: "Dinner in Rome".to_string(),
+            amount: 150,
+            amount_funded: 0,
+        }];
+        assert!(matches!(
+            apply_registry_contribution(&mut fund, 151, Some(7), 0),
+            Err(Message::InvalidInput(_))
+        ));
+        assert!(apply_registry_contribution(&mut fund, 150, Some(7), 0).is_ok());
+        assert_eq!(fund.experiences[0].amount_funded, 150);
+        // Funds keep taking contributions past their target
+        assert!(apply_registry_contribution(&mut fund, 400, None, 0).is_ok());
+        assert_eq!(fund.amount_funded, 550);
+        assert_eq!(fund.status, "fulfilled");
+        assert!(matches!(
+            apply_registry_contribution(&mut item, 1, Some(7), 0),
+            Err(Message::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn reservations_lapse_after_the_window_and_free_the_item() {
+        let guest = Principal::from_slice(&[1]);
+        let window = REGISTRY_RESERVATION_WINDOW.as_nanos() as u64;
+        let mut item = registry_item(100, 1);
+
+        assert!(reserve_registry_item_for(&mut item, guest, 0).is_ok());
+        assert!(reservation_active_at(&item, window - 1));
+        assert!(matches!(
+            reserve_registry_item_for(&mut item, Principal::from_slice(&[2]), window - 1),
+            Err(Message::Error(_))
+        ));
+        assert!(!release_lapsed_reservation(&mut item, window - 1));
+
+        assert!(!reservation_active_at(&item, window));
+        assert!(release_lapsed_reservation(&mut item, window));
+        assert_eq!(item.status, "available");
+        assert_eq!(item.reserved_by, None);
+        assert_eq!(item.reserved_until, None);
+    }
+
+    #[test]
+    fn only_the_reserving_guest_can_confirm_before_the_reservation_lapses() {
+        let guest = Principal::from_slice(&[1]);
+        let window = REGISTRY_RESERVATION_WINDOW.as_nanos() as u64;
+        let mut item = registry_item(100, 2);
+        assert!(reserve_registry_item_for(&mut item, guest, 0).is_ok());
+
+        assert!(matches!(
+            confirm_reserved_purchase(&mut item, Principal::from_slice(&[2]), "Ben", 1, "now"),
+            Err(Message::UnauthorizedAction(_))
+        ));
+        assert!(matches!(
+            confirm_reserved_purchase(&mut item, guest, "Ann", window, "now"),
+            Err(Message::Error(_))
+        ));
+        assert_eq!(item.quantity_received, 0);
+
+        assert!(confirm_reserved_purchase(&mut item, guest, "Ann", 1, "now").is_ok());
+        assert_eq!(item.quantity_received, 1);
+        assert_eq!(item.status, "available");
+        assert_eq!(item.reserved_by, None);
+
+        assert!(reserve_registry_item_for(&mut item, guest, 2).is_ok());
+        assert!(confirm_reserved_purchase(&mut item, guest, "Ann", 3, "now").is_ok());
+        assert_eq!(item.status, "purchased");
+        assert_eq!(item.purchases.len(), 2);
+    }
+
+    #[test]
+    fn invitation_codes_hash_with_the_wedding_salt_ignoring_case_and_spacing() {
+        assert_eq!(hash_code("salt", "ab12cd"), hash_code("salt", "  AB12CD\n"));
+        assert_ne!(hash_code("salt", "AB12CD"), hash_code("pepper", "AB12CD"));
+        assert_ne!(hash_code("salt", "AB12CD"), hash_code("salt", "AB12CE"));
+        assert_eq!(hash_code("salt", "AB12CD").len(), 64);
+    }
+
+    #[test]
+    fn an_invitation_binds_the_first_principal_that_redeems_it() {
+        let ann = Principal::from_slice(&[1]);
+        let ben = Principal::from_slice(&[2]);
+        let mut wedding = wedding("2030-06-01");
+        wedding.code_salt = "salt".to_string();
+        let mut invited = guest("Ann Smith", "ann@example.com");
+        invited.id = 7;
+        wedding.guest_list.push(invited);
+        wedding.invitations.push(Invitation {
+            id: 1,
+            code_hash: hash_code("salt", "AB12CD"),
+            guest_id: Some(7),
+            ..Default::default()
+        });
+        wedding.invitations.push(Invitation {
+            id: 2,
+            code_hash: hash_code("salt", "ZZ99ZZ"),
+            revoked: true,
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            redeem_invitation(&mut wedding, "AB12CD", Principal::anonymous()),
+            Err(Message::UnauthorizedAction(_))
+        ));
+        assert!(matches!(
+            redeem_invitation(&mut wedding, "AB12CE", ann),
+            Err(Message::UnauthorizedAction(_))
+        ));
+        assert!(matches!(
+            redeem_invitation(&mut wedding, "ZZ99ZZ", ann),
+            Err(Message::UnauthorizedAction(_))
+        ));
+        assert_eq!(wedding.guest_list[0].principal, None);
+
+        assert!(redeem_invitation(&mut wedding, "ab12cd", ann).is_ok());
+        assert_eq!(wedding.invitations[0].redeemed_by, Some(ann));
+        assert_eq!(wedding.guest_list[0].principal, Some(ann));
+
+        assert!(matches!(
+            redeem_invitation(&mut wedding, "AB12CD", ben),
+            Err(Message::UnauthorizedAction(_))
+        ));
+        assert!(redeem_invitation(&mut wedding, "AB12CD", ann).is_ok());
+        assert_eq!(wedding.guest_list[0].principal, Some(ann));
+    }
+
+    #[test]
+    fn released_seats_go_to_the_highest_priority_waitlisted_guests_who_fit() {
+        let mut wedding = wedding("2030-06-01");
+        for (id, name, status, plus_one, priority) in [
+            (1, "Ann", "waitlisted", true, 1),
+            (2, "Ben", "waitlisted", false, 5),
+            (3, "Cat", "declined", false, 9),
+        ] {
+            let mut waiting = guest(name, "");
+            waiting.id = id;
+            waiting.rsvp_status = status.to_string();
+            waiting.plus_one = plus_one;
+            wedding.guest_list.push(waiting);
+            wedding.waitlist.push(WaitlistEntry {
+                guest_id: id,
+                priority,
+                table_assignment: TableAssignment::Table(id as u8),
+                added_at: "".to_string(),
+            });
+        }
+
+        release_seats(&mut wedding, 2);
+        assert_eq!(wedding.guest_list[1].rsvp_status, "confirmed");
+        assert_eq!(
+            wedding.guest_list[1].table_assignment,
+            TableAssignment::Table(2)
+        );
+        assert_eq!(wedding.guest_list[0].rsvp_status, "waitlisted");
+        assert_eq!(wedding.guest_list[2].rsvp_status, "declined");
+        assert_eq!(wedding.guest_count, 1);
+        assert_eq!(
+            wedding
+                .waitlist
+                .iter()
+                .map(|entry| entry.guest_id)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+
+        release_seats(&mut wedding, 1);
+        assert_eq!(wedding.guest_list[0].rsvp_status, "confirmed");
+        assert_eq!(wedding.guest_count, 0);
+        assert!(wedding.waitlist.is_empty());
+    }
+}